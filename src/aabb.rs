@@ -0,0 +1,179 @@
+use crate::{
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Thickness given to the bounding boxes of flat, axis-aligned shapes.
+pub(crate) const BBOX_PADDING: f64 = 1e-4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Aabb {
+    minimum: Point3,
+    maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(a: Point3, b: Point3) -> Self {
+        Self {
+            minimum: a.min(&b),
+            maximum: a.max(&b),
+        }
+    }
+
+    pub fn min(&self) -> &Point3 {
+        &self.minimum
+    }
+
+    pub fn max(&self) -> &Point3 {
+        &self.maximum
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        (self.minimum + self.maximum) / 2
+    }
+
+    pub fn extent(&self) -> Vec3 {
+        self.maximum - self.minimum
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.extent();
+        return 2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x());
+    }
+
+    /// Index of the axis along which the box is the longest.
+    pub fn longest_axis(&self) -> usize {
+        let d = self.extent();
+        if d.x() > d.y() && d.x() > d.z() {
+            0
+        } else if d.y() > d.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Widens every axis thinner than `delta` to `delta`, so that flat shapes
+    /// still have a box rays can hit.
+    pub fn padded(&self, delta: f64) -> Aabb {
+        let d = self.extent();
+        let grow = |extent: f64| {
+            if extent < delta {
+                (delta - extent) / 2.0
+            } else {
+                0.0
+            }
+        };
+        let g = Vec3::new(grow(d.x()), grow(d.y()), grow(d.z()));
+        Aabb {
+            minimum: self.minimum - g,
+            maximum: self.maximum + g,
+        }
+    }
+
+    /// Grows the box by `margin` on every side.
+    pub fn expanded(&self, margin: f64) -> Aabb {
+        let m = Vec3::new(margin, margin, margin);
+        Aabb {
+            minimum: self.minimum - m,
+            maximum: self.maximum + m,
+        }
+    }
+
+    pub fn surrounding(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            minimum: self.minimum.min(&other.minimum),
+            maximum: self.maximum.max(&other.maximum),
+        }
+    }
+
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.clip(r, t_min, t_max).is_some()
+    }
+
+    /// Part of (`t_min`, `t_max`) where `r` is inside the box, if any.
+    pub fn clip(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inv_d = 1.0 / r.direction()[a];
+            let mut t0 = (self.minimum[a] - r.origin()[a]) * inv_d;
+            let mut t1 = (self.maximum[a] - r.origin()[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        return Some((t_min, t_max));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Aabb;
+    use crate::{
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn new_orders_corners() {
+        let bbox = Aabb::new(Point3::new(1, -1, 3), Point3::new(-1, 1, 2));
+        assert_eq!(*bbox.min(), Point3::new(-1, -1, 2));
+        assert_eq!(*bbox.max(), Point3::new(1, 1, 3));
+    }
+
+    #[test]
+    fn surrounding() {
+        let a = Aabb::new(Point3::new(0, 0, 0), Point3::new(1, 1, 1));
+        let b = Aabb::new(Point3::new(-1, 0.5, 0.5), Point3::new(0.5, 2, 0.5));
+        let c = a.surrounding(&b);
+        assert_eq!(*c.min(), Point3::new(-1, 0, 0));
+        assert_eq!(*c.max(), Point3::new(1, 2, 1));
+    }
+
+    #[test]
+    fn clip() {
+        let bbox = Aabb::new(Point3::new(-1, -1, -1), Point3::new(1, 1, 1)).expanded(1.0);
+        let r = Ray::new(Point3::new(-5, 0, 0), Vec3::new(1, 0, 0));
+        assert_eq!(bbox.clip(&r, 0.0, f64::INFINITY), Some((3.0, 7.0)));
+        assert_eq!(bbox.clip(&r, 4.0, 5.0), Some((4.0, 5.0)));
+        assert_eq!(bbox.clip(&r, 8.0, f64::INFINITY), None);
+        let above = Ray::new(Point3::new(-5, 2.5, 0), Vec3::new(1, 0, 0));
+        assert_eq!(bbox.clip(&above, 0.0, f64::INFINITY), None);
+    }
+
+    #[test]
+    fn surface_area() {
+        let bbox = Aabb::new(Point3::new(0, 0, 0), Point3::new(1, 2, 3));
+        assert_eq!(bbox.surface_area(), 22.0);
+        assert_eq!(bbox.longest_axis(), 2);
+    }
+
+    #[test]
+    fn padded() {
+        let flat = Aabb::new(Point3::new(0, 0, 0), Point3::new(2, 2, 0));
+        let padded = flat.padded(0.5);
+        assert_eq!(*padded.min(), Point3::new(0, 0, -0.25));
+        assert_eq!(*padded.max(), Point3::new(2, 2, 0.25));
+        let r = Ray::new(Point3::new(1, 1, 1), Vec3::new(0, 0, -1));
+        assert!(!flat.hit(&r, 0.001, f64::INFINITY));
+        assert!(padded.hit(&r, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn hit() {
+        let bbox = Aabb::new(Point3::new(-1, -1, -1), Point3::new(1, 1, 1));
+        let toward = Ray::new(Point3::new(0, 0, 5), Vec3::new(0, 0, -1));
+        let away = Ray::new(Point3::new(0, 0, 5), Vec3::new(0, 0, 1));
+        let beside = Ray::new(Point3::new(2, 0, 5), Vec3::new(0, 0, -1));
+        assert!(bbox.hit(&toward, 0.001, f64::INFINITY));
+        assert!(!bbox.hit(&toward, 0.001, 3.0));
+        assert!(!bbox.hit(&away, 0.001, f64::INFINITY));
+        assert!(!bbox.hit(&beside, 0.001, f64::INFINITY));
+    }
+}
@@ -0,0 +1,298 @@
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable, HittableList},
+    ray::Ray,
+};
+
+/// Number of buckets the centroids are binned into when evaluating the
+/// surface area heuristic.
+const SAH_BUCKETS: usize = 12;
+/// Leaves are allowed to hold this many objects when splitting them further
+/// would not pay off.
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of visiting a node relative to intersecting one object.
+const TRAVERSAL_COST: f64 = 0.125;
+
+type Entry<'a> = (Box<dyn Hittable + 'a>, Aabb);
+
+pub struct BvhNode<'a> {
+    left: Box<dyn Hittable + 'a>,
+    right: Option<Box<dyn Hittable + 'a>>,
+    bbox: Aabb,
+}
+
+impl<'a> BvhNode<'a> {
+    /// Builds a hierarchy over every object of `list` using the surface area
+    /// heuristic.
+    ///
+    /// Panics if the list is empty or if one of its objects has no bounding
+    /// box; unbounded objects should be kept next to the hierarchy instead.
+    pub fn new(list: HittableList<'a>) -> Self {
+        assert!(!list.is_empty(), "BvhNode needs at least one object");
+        let entries = list
+            .into_objects()
+            .into_iter()
+            .map(|object| {
+                let bbox = object
+                    .bounding_box()
+                    .expect("every object in a BvhNode needs a bounding box");
+                (object, bbox)
+            })
+            .collect();
+        Self::build(entries)
+    }
+
+    fn build(mut entries: Vec<Entry<'a>>) -> Self {
+        let bbox = entries
+            .iter()
+            .skip(1)
+            .fold(entries[0].1, |acc, (_, b)| acc.surrounding(b));
+
+        if entries.len() == 1 {
+            let (object, _) = entries.pop().unwrap();
+            return BvhNode {
+                left: object,
+                right: None,
+                bbox,
+            };
+        }
+        if entries.len() == 2 {
+            let (right, _) = entries.pop().unwrap();
+            let (left, _) = entries.pop().unwrap();
+            return BvhNode {
+                left,
+                right: Some(right),
+                bbox,
+            };
+        }
+
+        let right_entries = match Self::sah_split(&mut entries, &bbox) {
+            Some(right_entries) => right_entries,
+            None if entries.len() <= MAX_LEAF_SIZE => {
+                let mut leaf = HittableList::new();
+                for (object, _) in entries {
+                    leaf.add_boxed(object);
+                }
+                return BvhNode {
+                    left: Box::new(leaf),
+                    right: None,
+                    bbox,
+                };
+            }
+            None => Self::median_split(&mut entries),
+        };
+
+        BvhNode {
+            left: Self::subtree(entries),
+            right: Some(Self::subtree(right_entries)),
+            bbox,
+        }
+    }
+
+    fn subtree(mut entries: Vec<Entry<'a>>) -> Box<dyn Hittable + 'a> {
+        if entries.len() == 1 {
+            return entries.pop().unwrap().0;
+        }
+        Box::new(Self::build(entries))
+    }
+
+    /// Splits `entries` at the cheapest bucket boundary and returns the right
+    /// half, or `None` when no split is cheaper than a single leaf.
+    fn sah_split(entries: &mut Vec<Entry<'a>>, bbox: &Aabb) -> Option<Vec<Entry<'a>>> {
+        let centroid_bounds = entries.iter().skip(1).fold(
+            Aabb::new(entries[0].1.centroid(), entries[0].1.centroid()),
+            |acc, (_, b)| acc.surrounding(&Aabb::new(b.centroid(), b.centroid())),
+        );
+
+        let bucket_of = |b: &Aabb, axis: usize| -> usize {
+            let lo = centroid_bounds.min()[axis];
+            let extent = centroid_bounds.max()[axis] - lo;
+            let i = ((b.centroid()[axis] - lo) / extent * SAH_BUCKETS as f64) as usize;
+            i.min(SAH_BUCKETS - 1)
+        };
+
+        let mut best: Option<(f64, usize, usize)> = None;
+        for axis in 0..3 {
+            if centroid_bounds.max()[axis] - centroid_bounds.min()[axis] <= 0.0 {
+                continue;
+            }
+            let mut counts = [0usize; SAH_BUCKETS];
+            let mut bounds: [Option<Aabb>; SAH_BUCKETS] = [None; SAH_BUCKETS];
+            for (_, b) in entries.iter() {
+                let i = bucket_of(b, axis);
+                counts[i] += 1;
+                bounds[i] = Some(bounds[i].map_or(*b, |acc| acc.surrounding(b)));
+            }
+
+            // Sweep from the right once to collect the cost of every suffix.
+            let mut right_cost = [0.0; SAH_BUCKETS];
+            let mut acc: Option<Aabb> = None;
+            let mut count = 0;
+            for i in (1..SAH_BUCKETS).rev() {
+                if let Some(b) = bounds[i] {
+                    acc = Some(acc.map_or(b, |a| a.surrounding(&b)));
+                }
+                count += counts[i];
+                right_cost[i] = acc.map_or(0.0, |a| a.surface_area() * count as f64);
+            }
+
+            let mut acc: Option<Aabb> = None;
+            let mut count = 0;
+            for split in 1..SAH_BUCKETS {
+                if let Some(b) = bounds[split - 1] {
+                    acc = Some(acc.map_or(b, |a| a.surrounding(&b)));
+                }
+                count += counts[split - 1];
+                let left_cost = acc.map_or(0.0, |a| a.surface_area() * count as f64);
+                let cost = TRAVERSAL_COST + (left_cost + right_cost[split]) / bbox.surface_area();
+                if best.is_none_or(|(c, _, _)| cost < c) {
+                    best = Some((cost, axis, split));
+                }
+            }
+        }
+
+        let (cost, axis, split) = best?;
+        if cost >= entries.len() as f64 && entries.len() <= MAX_LEAF_SIZE {
+            return None;
+        }
+        let (left, right): (Vec<_>, Vec<_>) = entries
+            .drain(..)
+            .partition(|(_, b)| bucket_of(b, axis) < split);
+        if left.is_empty() || right.is_empty() {
+            entries.extend(left.into_iter().chain(right));
+            return None;
+        }
+        *entries = left;
+        Some(right)
+    }
+
+    fn median_split(entries: &mut Vec<Entry<'a>>) -> Vec<Entry<'a>> {
+        let axis = entries
+            .iter()
+            .skip(1)
+            .fold(entries[0].1, |acc, (_, b)| acc.surrounding(b))
+            .longest_axis();
+        entries.sort_by(|(_, a), (_, b)| a.centroid()[axis].total_cmp(&b.centroid()[axis]));
+        entries.split_off(entries.len() / 2)
+    }
+}
+
+impl<'a> Hittable for BvhNode<'a> {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return false;
+        }
+        let hit_left = self.left.hit(r, t_min, t_max, rec, rng);
+        let hit_right = match &self.right {
+            Some(right) => right.hit(r, t_min, if hit_left { rec.t } else { t_max }, rec, rng),
+            None => false,
+        };
+        return hit_left || hit_right;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::BvhNode;
+    use crate::{
+        hittable::{test_rng, HitRecord, Hittable, HittableList},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        sphere::Sphere,
+        vec3::{Point3, Vec3},
+    };
+
+    fn random_scene(
+        rng: &mut StdRng,
+        count: usize,
+    ) -> (HittableList<'static>, HittableList<'static>) {
+        let mut list = HittableList::new();
+        let mut copy = HittableList::new();
+        for _ in 0..count {
+            let center = Vec3::random_range(rng, -10.0..10.0);
+            let radius = rng.gen_range(0.05..1.0);
+            list.add(Sphere::new(center, radius, Lambertian::default()));
+            copy.add(Sphere::new(center, radius, Lambertian::default()));
+        }
+        (list, copy)
+    }
+
+    fn assert_same_hits(count: usize, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (list, copy) = random_scene(&mut rng, count);
+        let bvh = BvhNode::new(copy);
+        assert_eq!(bvh.bounding_box(), list.bounding_box());
+
+        for _ in 0..2000 {
+            let origin = Vec3::random_range(&mut rng, -15.0..15.0);
+            let target = Vec3::random_range(&mut rng, -10.0..10.0);
+            let r = Ray::new(origin, target - origin);
+
+            let mut expected = HitRecord::default();
+            let mut actual = HitRecord::default();
+            let hit_list = list.hit(&r, 0.001, f64::INFINITY, &mut expected, &mut test_rng());
+            let hit_bvh = bvh.hit(&r, 0.001, f64::INFINITY, &mut actual, &mut test_rng());
+            assert_eq!(hit_list, hit_bvh);
+            if hit_list {
+                assert_eq!(expected.t, actual.t);
+                assert_eq!(expected.p, actual.p);
+                assert_eq!(expected.normal, actual.normal);
+            }
+        }
+    }
+
+    #[test]
+    fn single_object() {
+        assert_same_hits(1, 1);
+    }
+
+    #[test]
+    fn small_scene_matches_list() {
+        assert_same_hits(7, 2);
+    }
+
+    #[test]
+    fn random_scene_matches_list() {
+        assert_same_hits(500, 3);
+    }
+
+    #[test]
+    fn coincident_objects_match_list() {
+        let mut list = HittableList::new();
+        let mut copy = HittableList::new();
+        for _ in 0..20 {
+            list.add(Sphere::new(
+                Point3::new(0, 0, 0),
+                1.0,
+                Lambertian::default(),
+            ));
+            copy.add(Sphere::new(
+                Point3::new(0, 0, 0),
+                1.0,
+                Lambertian::default(),
+            ));
+        }
+        let bvh = BvhNode::new(copy);
+        let r = Ray::new(Point3::new(0, 0, 5), Vec3::new(0, 0, -1));
+        let mut expected = HitRecord::default();
+        let mut actual = HitRecord::default();
+        assert!(list.hit(&r, 0.001, f64::INFINITY, &mut expected, &mut test_rng()));
+        assert!(bvh.hit(&r, 0.001, f64::INFINITY, &mut actual, &mut test_rng()));
+        assert_eq!(expected.t, actual.t);
+    }
+}
@@ -1,3 +1,5 @@
+use rand::Rng;
+
 use crate::{
     ray::Ray,
     vec3::{Point3, Vec3},
@@ -6,19 +8,82 @@ use crate::{
 pub struct Camera {
     aspect_ratio: f64,
     viewport_height: f64,
-    focal_length: f64,
+    focus_dist: f64,
     origin: Point3,
+    lower_left_corner: Point3,
+    horizontal: Vec3,
+    vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    lens_radius: f64,
+    shutter_open: f64,
+    shutter_close: f64,
 }
 
 impl Camera {
-    pub fn new(aspect_ratio: f64, viewport_height: f64, focal_length: f64, origin: Point3) -> Self {
+    /// Camera at `lookfrom` looking towards `lookat`, with `vup` pointing up.
+    /// `vfov` is the vertical field of view in degrees. Points `focus_dist`
+    /// away are in focus; an `aperture` of zero gives a pinhole camera.
+    pub fn new(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        vfov: f64,
+        aspect_ratio: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> Self {
+        let h = (vfov.to_radians() / 2.0).tan();
+        Self::with_viewport(
+            lookfrom,
+            lookat,
+            vup,
+            2.0 * h,
+            aspect_ratio,
+            aperture,
+            focus_dist,
+        )
+    }
+
+    fn with_viewport(
+        lookfrom: Point3,
+        lookat: Point3,
+        vup: Vec3,
+        viewport_height: f64,
+        aspect_ratio: f64,
+        aperture: f64,
+        focus_dist: f64,
+    ) -> Self {
+        let viewport_width = viewport_height * aspect_ratio;
+        let w = (lookfrom - lookat).unit();
+        let u = vup.cross(&w).unit();
+        let v = w.cross(&u);
+
+        let horizontal = u * (viewport_width * focus_dist);
+        let vertical = v * (viewport_height * focus_dist);
         Self {
             aspect_ratio,
             viewport_height,
-            focal_length,
-            origin,
+            focus_dist,
+            origin: lookfrom,
+            lower_left_corner: lookfrom - horizontal / 2 - vertical / 2 - w * focus_dist,
+            horizontal,
+            vertical,
+            u,
+            v,
+            lens_radius: aperture / 2.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
+
+    /// Keeps the shutter open from `open` to `close`; every ray gets a time
+    /// drawn uniformly in that interval, which blurs moving objects.
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.shutter_open = open;
+        self.shutter_close = close;
+        self
+    }
     pub fn aspect_ratio(&self) -> f64 {
         self.aspect_ratio
     }
@@ -28,45 +93,77 @@ impl Camera {
     pub fn viewport_width(&self) -> f64 {
         self.viewport_height() * self.aspect_ratio()
     }
-    pub fn focal_length(&self) -> f64 {
-        self.focal_length
+    pub fn focus_dist(&self) -> f64 {
+        self.focus_dist
     }
     pub fn origin(&self) -> Point3 {
         self.origin
     }
     pub fn horizontal(&self) -> Vec3 {
-        Vec3::new(self.viewport_width(), 0, 0)
+        self.horizontal
     }
     pub fn vertical(&self) -> Vec3 {
-        Vec3::new(0, self.viewport_height(), 0)
+        self.vertical
     }
     pub fn lower_left_corner(&self) -> Point3 {
-        self.origin
-            - (self.horizontal() / 2)
-            - (self.vertical() / 2)
-            - Vec3::new(0, 0, self.focal_length)
-    }
-    pub fn get_ray(&self, u: f64, v: f64) -> Ray {
-        Ray::new(
-            self.origin,
-            self.lower_left_corner() + (self.horizontal() * u) + (self.vertical() * v)
-                - self.origin(),
+        self.lower_left_corner
+    }
+    pub fn lens_radius(&self) -> f64 {
+        self.lens_radius
+    }
+    pub fn shutter(&self) -> (f64, f64) {
+        (self.shutter_open, self.shutter_close)
+    }
+    /// Ray through the viewport at (`s`, `t`), starting from a random point
+    /// on the lens.
+    pub fn get_ray<R: Rng + ?Sized>(&self, s: f64, t: f64, rng: &mut R) -> Ray {
+        let offset = if self.lens_radius > 0.0 {
+            let rd = Vec3::random_in_unit_disk(rng) * self.lens_radius;
+            self.u * rd.x() + self.v * rd.y()
+        } else {
+            Vec3::default()
+        };
+        let time = if self.shutter_close > self.shutter_open {
+            rng.gen_range(self.shutter_open..self.shutter_close)
+        } else {
+            self.shutter_open
+        };
+        Ray::with_time(
+            self.origin + offset,
+            self.lower_left_corner + (self.horizontal * s) + (self.vertical * t)
+                - self.origin
+                - offset,
+            time,
         )
     }
 }
 
 impl Default for Camera {
     fn default() -> Self {
-        Camera::new(16.0 / 9.0, 2.0, 1.0, Point3::new(0, 0, 0))
+        Camera::with_viewport(
+            Point3::new(0, 0, 0),
+            Point3::new(0, 0, -1),
+            Vec3::new(0, 1, 0),
+            2.0,
+            16.0 / 9.0,
+            0.0,
+            1.0,
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::vec3::Point3;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::vec3::{Point3, Vec3};
 
     use super::Camera;
 
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
     #[test]
     fn default_aspect_ratio() {
         assert_eq!(Camera::default().aspect_ratio(), 16.0 / 9.0)
@@ -83,12 +180,92 @@ mod tests {
     }
 
     #[test]
-    fn default_focal_length() {
-        assert_eq!(Camera::default().focal_length(), 1.0)
+    fn default_focus_dist() {
+        assert_eq!(Camera::default().focus_dist(), 1.0)
     }
 
     #[test]
     fn default_origin() {
         assert_eq!(Camera::default().origin(), Point3::new(0, 0, 0))
     }
+
+    #[test]
+    fn default_lower_left_corner() {
+        assert_eq!(
+            Camera::default().lower_left_corner(),
+            Point3::new(-16.0 / 9.0, -1.0, -1.0)
+        )
+    }
+
+    #[test]
+    fn looks_at_target() {
+        let camera = Camera::new(
+            Point3::new(3, 3, 2),
+            Point3::new(0, 0, -1),
+            Vec3::new(0, 1, 0),
+            20.0,
+            16.0 / 9.0,
+            0.0,
+            1.0,
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+        let r = camera.get_ray(0.5, 0.5, &mut rng);
+        assert_close(*r.origin(), Point3::new(3, 3, 2));
+        assert_close(r.direction().unit(), Vec3::new(-1, -1, -1).unit());
+    }
+
+    #[test]
+    fn vertical_fov() {
+        let camera = Camera::new(
+            Point3::new(0, 0, 0),
+            Point3::new(0, 0, -1),
+            Vec3::new(0, 1, 0),
+            60.0,
+            2.0,
+            0.0,
+            1.0,
+        );
+        let top = camera.lower_left_corner() + camera.horizontal() / 2 + camera.vertical();
+        let angle = top.unit().dot(&Vec3::new(0, 0, -1)).acos().to_degrees();
+        assert!((angle - 30.0).abs() < 1e-9);
+        assert!((camera.viewport_width() - 2.0 * camera.viewport_height()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn defocus_converges_on_focus_plane() {
+        let camera = Camera::new(
+            Point3::new(0, 0, 0),
+            Point3::new(0, 0, -1),
+            Vec3::new(0, 1, 0),
+            90.0,
+            1.0,
+            2.0,
+            5.0,
+        );
+        let mut rng = StdRng::seed_from_u64(1);
+        let target =
+            camera.lower_left_corner() + camera.horizontal() * 0.3 + camera.vertical() * 0.6;
+        for _ in 0..100 {
+            let r = camera.get_ray(0.3, 0.6, &mut rng);
+            assert!((*r.origin() - camera.origin()).length() <= camera.lens_radius());
+            assert_close(r.at(1.0), target);
+        }
+    }
+
+    #[test]
+    fn shutter_times() {
+        let mut rng = StdRng::seed_from_u64(2);
+        assert_eq!(Camera::default().get_ray(0.5, 0.5, &mut rng).time(), 0.0);
+
+        let instant = Camera::default().with_shutter(0.25, 0.25);
+        assert_eq!(instant.get_ray(0.5, 0.5, &mut rng).time(), 0.25);
+
+        let camera = Camera::default().with_shutter(1.0, 1.5);
+        assert_eq!(camera.shutter(), (1.0, 1.5));
+        let times: Vec<f64> = (0..200)
+            .map(|_| camera.get_ray(0.5, 0.5, &mut rng).time())
+            .collect();
+        assert!(times.iter().all(|t| (1.0..1.5).contains(t)));
+        assert!(times.iter().any(|t| *t < 1.1) && times.iter().any(|t| *t > 1.4));
+    }
 }
@@ -0,0 +1,231 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::{
+    film::{FilmError, OutputFormat},
+    render::RenderSettings,
+    tonemap::{Operator, ToneMap},
+};
+
+fn positive(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(x) if x > 0.0 && x.is_finite() => Ok(x),
+        Ok(_) => Err(String::from("must be a positive number")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Render a scene description to an image.
+#[derive(Parser, Debug)]
+#[command(version)]
+pub struct Args {
+    /// Scene description to render
+    #[arg(default_value = "scenes/default.toml")]
+    pub scene: PathBuf,
+
+    /// Output image; the extension selects the format, with .exr, .hdr and
+    /// .pfm keeping linear floating-point radiance
+    #[arg(short, long, default_value = "result.png")]
+    pub output: PathBuf,
+
+    /// Image width in pixels, overriding the scene (height follows the aspect ratio)
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(2..))]
+    pub width: Option<u32>,
+
+    /// Samples per pixel, overriding the scene
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: Option<u32>,
+
+    /// Maximum number of bounces per path, overriding the scene
+    #[arg(short = 'd', long)]
+    pub max_depth: Option<usize>,
+
+    /// Bounces after which paths may be ended at random, overriding the scene
+    #[arg(long)]
+    pub roulette_depth: Option<usize>,
+
+    /// Seed for every random decision, overriding the scene
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Tone curve used for 8-bit output
+    #[arg(long, value_enum, default_value_t = Operator::Clamp)]
+    pub tonemap: Operator,
+
+    /// Exposure adjustment in stops before tone mapping
+    #[arg(short, long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub exposure: f64,
+
+    /// Linear white point of the reinhard-extended and hable curves
+    #[arg(long, default_value_t = ToneMap::default().white, value_parser = positive)]
+    pub white: f64,
+
+    /// Number of worker threads [default: one per CPU core]
+    #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Do not show the progress bar
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+impl Args {
+    /// Applies the overrides given on the command line to the scene settings.
+    pub fn apply(&self, settings: &mut RenderSettings, aspect_ratio: f64) -> Result<(), String> {
+        if let Some(width) = self.width {
+            let height = (width as f64 / aspect_ratio) as u32;
+            if height < 2 {
+                return Err(format!(
+                    "width {} gives an image height of {}, which is too small",
+                    width, height
+                ));
+            }
+            settings.image_width = width;
+            settings.image_height = height;
+        }
+        if let Some(samples) = self.samples {
+            settings.samples_per_pixel = samples as usize;
+        }
+        if let Some(max_depth) = self.max_depth {
+            settings.max_depth = max_depth;
+        }
+        if let Some(roulette_depth) = self.roulette_depth {
+            settings.roulette_depth = roulette_depth;
+        }
+        if let Some(seed) = self.seed {
+            settings.seed = seed;
+        }
+        Ok(())
+    }
+
+    pub fn tone_map(&self) -> ToneMap {
+        ToneMap {
+            operator: self.tonemap,
+            exposure: self.exposure,
+            white: self.white,
+        }
+    }
+
+    /// Checks that the output format is one we can write before spending
+    /// time on the render.
+    pub fn check_output(&self) -> Result<OutputFormat, FilmError> {
+        OutputFormat::from_path(&self.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::Args;
+    use crate::{
+        render::{Background, RenderSettings},
+        tonemap::{Operator, ToneMap},
+    };
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            image_width: 384,
+            image_height: 216,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            roulette_depth: 5,
+            seed: 0,
+            background: Background::default(),
+            fog: None,
+        }
+    }
+
+    #[test]
+    fn defaults_keep_scene_settings() {
+        let args = Args::try_parse_from(["raytracer"]).unwrap();
+        let mut s = settings();
+        args.apply(&mut s, 16.0 / 9.0).unwrap();
+        assert_eq!(s.image_width, 384);
+        assert_eq!(s.samples_per_pixel, 100);
+        assert_eq!(s.roulette_depth, 5);
+        assert_eq!(args.output.to_str(), Some("result.png"));
+        assert!(!args.quiet);
+        assert_eq!(args.tone_map(), ToneMap::default());
+    }
+
+    #[test]
+    fn tone_mapping() {
+        let args = Args::try_parse_from([
+            "raytracer",
+            "--tonemap",
+            "reinhard-extended",
+            "-e",
+            "-1.5",
+            "--white",
+            "4",
+        ])
+        .unwrap();
+        let tone_map = args.tone_map();
+        assert_eq!(tone_map.operator, Operator::ReinhardExtended);
+        assert_eq!(tone_map.exposure, -1.5);
+        assert_eq!(tone_map.white, 4.0);
+        assert!(Args::try_parse_from(["raytracer", "--tonemap", "filmic"]).is_err());
+        assert!(Args::try_parse_from(["raytracer", "--white", "0"]).is_err());
+    }
+
+    #[test]
+    fn overrides() {
+        let args = Args::try_parse_from([
+            "raytracer",
+            "scene.toml",
+            "-w",
+            "200",
+            "-s",
+            "8",
+            "-d",
+            "5",
+            "--roulette-depth",
+            "2",
+            "--seed",
+            "3",
+            "-j",
+            "2",
+            "-q",
+            "-o",
+            "out.png",
+        ])
+        .unwrap();
+        let mut s = settings();
+        args.apply(&mut s, 2.0).unwrap();
+        assert_eq!(args.scene.to_str(), Some("scene.toml"));
+        assert_eq!((s.image_width, s.image_height), (200, 100));
+        assert_eq!(s.samples_per_pixel, 8);
+        assert_eq!(s.max_depth, 5);
+        assert_eq!(s.roulette_depth, 2);
+        assert_eq!(s.seed, 3);
+        assert_eq!(args.threads, Some(2));
+        assert!(args.quiet);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(Args::try_parse_from(["raytracer", "-s", "0"]).is_err());
+        assert!(Args::try_parse_from(["raytracer", "-w", "1"]).is_err());
+        assert!(Args::try_parse_from(["raytracer", "-j", "0"]).is_err());
+        assert!(Args::try_parse_from(["raytracer", "--seed", "-1"]).is_err());
+        assert!(Args::try_parse_from(["raytracer", "-d", "deep"]).is_err());
+    }
+
+    #[test]
+    fn rejects_too_short_image() {
+        let args = Args::try_parse_from(["raytracer", "-w", "3"]).unwrap();
+        assert!(args.apply(&mut settings(), 2.0).is_err());
+    }
+
+    #[test]
+    fn checks_output_format() {
+        let args = Args::try_parse_from(["raytracer", "-o", "out.jpg"]).unwrap();
+        assert!(args.check_output().is_ok());
+        let args = Args::try_parse_from(["raytracer", "-o", "out.exr"]).unwrap();
+        assert!(args.check_output().unwrap().is_hdr());
+        let args = Args::try_parse_from(["raytracer", "-o", "out.xyz"]).unwrap();
+        assert!(args.check_output().is_err());
+    }
+}
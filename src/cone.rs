@@ -0,0 +1,202 @@
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    cylinder::{axial_u, hit_cap},
+    hittable::{crossing_intervals, HitRecord, Hittable, Interval},
+    material::Material,
+    poly::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Cone around the Y axis with a base of `radius` at the origin and its
+/// apex at `height`, closed by a disk at the base if `capped`. Place it with
+/// a `Transform`. On the side `u` goes around the axis and `v` up to the
+/// apex; the cap uses the polar coordinates of a `Disk`.
+pub struct Cone {
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Cone {
+    pub fn new<M: Material + 'static>(radius: f64, height: f64, capped: bool, material: M) -> Self {
+        Cone {
+            radius,
+            height,
+            capped,
+            material: Box::new(material),
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn capped(&self) -> bool {
+        self.capped
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let (o, d) = (r.origin(), r.direction());
+        let mut closest = t_max;
+        // Outward normal and `v` of the closest hit so far.
+        let mut found: Option<(Vec3, f64)> = None;
+
+        // x² + z² = k² (h - y)², the double cone with its apex at `height`.
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let below_apex = self.height - o.y();
+        let a = d.x() * d.x() + d.z() * d.z() - k2 * d.y() * d.y();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z() + k2 * below_apex * d.y());
+        let c = o.x() * o.x() + o.z() * o.z() - k2 * below_apex * below_apex;
+        for t in solve_quadratic(a, b, c) {
+            if t <= t_min || t >= closest {
+                continue;
+            }
+            let p = r.at(t);
+            // Only the lower nappe, between the base and the apex.
+            if (0.0..=self.height).contains(&p.y()) {
+                closest = t;
+                let normal = Vec3::new(p.x(), k2 * (self.height - p.y()), p.z());
+                let normal = if normal.length_squared() > 0.0 {
+                    normal.unit()
+                } else {
+                    Vec3::new(0, 1, 0)
+                };
+                found = Some((normal, p.y() / self.height));
+                break;
+            }
+        }
+        if self.capped {
+            if let Some((t, v)) = hit_cap(r, 0.0, self.radius, t_min, closest) {
+                closest = t;
+                found = Some((Vec3::new(0, -1, 0), v));
+            }
+        }
+
+        let (normal, v) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+        rec.t = closest;
+        rec.p = r.at(closest);
+        rec.set_face_normal(r, &normal);
+        rec.u = axial_u(&rec.p);
+        rec.v = v;
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Point3::new(-self.radius, 0, -self.radius),
+            Point3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.capped
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cone;
+    use crate::{
+        hittable::hit,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn side_and_cap() {
+        let cone = Cone::new(1.0, 1.0, true, Lambertian::default());
+        let rec = hit(
+            &cone,
+            &Ray::new(Point3::new(-5, 0.5, 0), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert!((rec.t - 4.5).abs() < 1e-12);
+        assert_close(rec.normal, Vec3::new(-1, 1, 0).unit());
+        assert!(rec.front_face);
+        assert_eq!(rec.v, 0.5);
+
+        let rec = hit(
+            &cone,
+            &Ray::new(Point3::new(0.5, -5, 0), Vec3::new(0, 1, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 5.0);
+        assert_close(rec.normal, Vec3::new(0, -1, 0));
+
+        // The upper nappe of the double cone is not part of the shape.
+        assert!(hit(
+            &cone,
+            &Ray::new(Point3::new(-5, 1.5, 0), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+        let open = Cone::new(1.0, 1.0, false, Lambertian::default());
+        let rec = hit(&open, &Ray::new(Point3::new(0, -5, 0), Vec3::new(0, 1, 0))).unwrap();
+        assert!((rec.t - 6.0).abs() < 1e-9);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn tangent_and_grazing_rays() {
+        let cone = Cone::new(1.0, 2.0, false, Lambertian::default());
+        // Tangent to the circle of radius 0.5 at half height.
+        let rec = hit(
+            &cone,
+            &Ray::new(Point3::new(-5, 1, 0.5), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert!((rec.t - 5.0).abs() < 1e-9);
+        // Perpendicular to the ray, so its side is arbitrary.
+        assert!((rec.normal.dot(&Vec3::new(0, 0.5, 1).unit()).abs() - 1.0).abs() < 1e-9);
+        assert!(hit(
+            &cone,
+            &Ray::new(Point3::new(-5, 1, 0.5 + 1e-9), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+        let rec = hit(
+            &cone,
+            &Ray::new(Point3::new(-5, 1, 0.5 - 1e-9), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert!(rec.p.x().abs() < 1e-3);
+
+        // Lying along a line of the surface the ray touches everywhere;
+        // that is not a hit.
+        let along = Ray::new(Point3::new(1.5, -1, 0), Vec3::new(-1, 2, 0));
+        assert!(hit(&cone, &along).is_none());
+        // Through the apex the normal falls back to the axis.
+        let rec = hit(&cone, &Ray::new(Point3::new(0, 5, 0), Vec3::new(0, -1, 0))).unwrap();
+        assert_eq!(rec.t, 3.0);
+        assert_close(rec.normal, Vec3::new(0, 1, 0));
+    }
+}
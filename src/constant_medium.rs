@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use rand::{Rng, RngCore};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::Vec3,
+};
+
+/// Smoke or mist of uniform `density` filling a closed boundary. A ray
+/// crossing it scatters after a distance drawn from the exponential
+/// distribution of the density, drawn from the renderer's RNG, and
+/// passes through unaffected otherwise. The scattering itself is up to the
+/// phase function, usually `Isotropic`.
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    density: f64,
+    phase_function: Box<dyn Material + 'static>,
+}
+
+impl ConstantMedium {
+    /// Panics unless the boundary is closed.
+    pub fn new<H: Hittable + 'static, M: Material + 'static>(
+        boundary: H,
+        density: f64,
+        phase_function: M,
+    ) -> Self {
+        Self::shared(Arc::new(boundary), density, phase_function)
+    }
+
+    pub fn shared<M: Material + 'static>(
+        boundary: Arc<dyn Hittable>,
+        density: f64,
+        phase_function: M,
+    ) -> Self {
+        assert!(
+            boundary.is_closed(),
+            "the boundary of a medium must be a closed object"
+        );
+        ConstantMedium {
+            boundary,
+            density,
+            phase_function: Box::new(phase_function),
+        }
+    }
+
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let intervals = self.boundary.intervals(r, rng);
+        if intervals.is_empty() {
+            return false;
+        }
+        // Optical depth the ray gets through before it scatters.
+        let mut depth = -(1.0 - rng.gen::<f64>()).ln();
+        let length = r.direction().length();
+        for interval in intervals {
+            let start = interval.enter.t.max(t_min);
+            let end = interval.exit.t.min(t_max);
+            if start >= end {
+                continue;
+            }
+            let through = (end - start) * length * self.density;
+            if depth < through {
+                rec.t = start + depth / (length * self.density);
+                rec.p = r.at(rec.t);
+                // Media have no surface to face.
+                rec.normal = Vec3::new(1, 0, 0);
+                rec.front_face = true;
+                (rec.u, rec.v) = (0.0, 0.0);
+                rec.material = self.phase_function.clone_box();
+                return true;
+            }
+            depth -= through;
+        }
+        return false;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::ConstantMedium;
+    use crate::{
+        csg::CsgUnion,
+        cuboid::Cuboid,
+        hittable::{hit, HitRecord, Hittable, HittableList},
+        material::{diffuse_light::DiffuseLight, lambertian::Lambertian},
+        quad::Quad,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn smoke(density: f64) -> ConstantMedium {
+        let boundary = Cuboid::new(
+            Point3::new(-1, -1, -3),
+            Point3::new(1, 1, -1),
+            Lambertian::default(),
+        );
+        ConstantMedium::new(boundary, density, DiffuseLight::new(Color::new(1, 2, 3)))
+    }
+
+    /// Fraction of `n` rays from `origin` in `direction` that scatter in
+    /// `medium`.
+    fn scattered_fraction(medium: &dyn Hittable, origin: Point3, direction: Vec3, n: usize) -> f64 {
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::new(origin, direction);
+        let hits = (0..n)
+            .filter(|_| {
+                let mut rec = HitRecord::default();
+                medium.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng)
+            })
+            .count();
+        return hits as f64 / n as f64;
+    }
+
+    #[test]
+    fn scatters_with_beer_lambert_probability() {
+        let origin = Point3::new(0, 0, 0);
+        for density in [0.1, 0.5, 2.0] {
+            let fraction = scattered_fraction(&smoke(density), origin, Vec3::new(0, 0, -1), 4000);
+            let expected = 1.0 - (-2.0 * density).exp();
+            assert!(
+                (fraction - expected).abs() < 0.02,
+                "{} {}",
+                density,
+                fraction
+            );
+        }
+        // Ray lengths do not change the density.
+        let fraction = scattered_fraction(&smoke(0.5), origin, Vec3::new(0, 0, -4), 4000);
+        assert!((fraction - (1.0 - (-1.0_f64).exp())).abs() < 0.02);
+    }
+
+    #[test]
+    fn overlapping_media_scatter_independently() {
+        // Both media are entered at the same point, yet each draws its own
+        // distance, so together they are as thick as one of twice the
+        // density.
+        let mut list = HittableList::new();
+        list.add(smoke(0.25));
+        list.add(smoke(0.25));
+        let fraction = scattered_fraction(&list, Point3::new(0, 0, 0), Vec3::new(0, 0, -1), 4000);
+        assert!(
+            (fraction - (1.0 - (-1.0_f64).exp())).abs() < 0.02,
+            "{}",
+            fraction
+        );
+    }
+
+    #[test]
+    fn scatters_inside_the_boundary() {
+        let medium = smoke(1.0);
+        let r = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1));
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        for _ in 0..100 {
+            let mut rec = HitRecord::default();
+            if medium.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng) {
+                assert!(rec.t >= 1.0 && rec.t <= 3.0);
+                assert_eq!(rec.p, r.at(rec.t));
+                assert_eq!(rec.material.emitted(&rec), Color::new(1, 2, 3));
+            }
+        }
+        // Dense smoke scatters right at its edge; none is hit beyond t_max.
+        let dense = smoke(1e6);
+        assert!((hit(&dense, &r).unwrap().t - 1.0).abs() < 1e-5);
+        let mut rec = HitRecord::default();
+        assert!(!dense.hit(&r, 0.001, 0.9, &mut rec, &mut rng));
+        let miss = Ray::new(Point3::new(0, 2, 0), Vec3::new(0, 0, -1));
+        assert!(hit(&dense, &miss).is_none());
+    }
+
+    #[test]
+    fn starts_inside() {
+        // A ray scattered inside carries on from where it is.
+        let dense = smoke(1e6);
+        let r = Ray::new(Point3::new(0, 0, -2), Vec3::new(1, 0, 0));
+        assert!(hit(&dense, &r).unwrap().t < 1e-3 + 1e-5);
+    }
+
+    #[test]
+    fn crosses_every_part_of_the_boundary() {
+        let part = |x: f64| {
+            Cuboid::new(
+                Point3::new(x, -1, -1),
+                Point3::new(x + 1.0, 1, 1),
+                Lambertian::default(),
+            )
+        };
+        let medium = ConstantMedium::new(
+            CsgUnion::new(part(1.0), part(3.0)),
+            0.5,
+            Lambertian::default(),
+        );
+        let (origin, direction) = (Point3::new(0, 0, 0), Vec3::new(1, 0, 0));
+        // Two unit lengths of smoke with a gap between them.
+        let fraction = scattered_fraction(&medium, origin, direction, 4000);
+        assert!((fraction - (1.0 - (-1.0_f64).exp())).abs() < 0.02);
+        let r = Ray::new(origin, direction);
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        for _ in 0..100 {
+            let mut rec = HitRecord::default();
+            if medium.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng) {
+                assert!(!(rec.t > 2.0 && rec.t < 3.0), "{}", rec.t);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "closed object")]
+    fn open_boundary() {
+        let sheet = Quad::new(
+            Point3::new(0, 0, 0),
+            Vec3::new(1, 0, 0),
+            Vec3::new(0, 1, 0),
+            Lambertian::default(),
+        );
+        ConstantMedium::new(sheet, 1.0, Lambertian::default());
+    }
+}
@@ -0,0 +1,411 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable, Interval},
+    ray::Ray,
+};
+
+/// Combines the intervals of two closed objects, keeping the parts of the
+/// ray where `inside(in_left, in_right)` holds. Every surface keeps the
+/// record, material included, of the operand it comes from.
+fn combine(
+    left: Vec<Interval>,
+    right: Vec<Interval>,
+    inside: impl Fn(bool, bool) -> bool,
+) -> Vec<Interval> {
+    // Boundaries of both operands in order: the record, which operand it
+    // belongs to and whether the ray enters that operand there.
+    let mut events = vec![];
+    for (operand, intervals) in [left, right].into_iter().enumerate() {
+        for interval in intervals {
+            events.push((interval.enter, operand, true));
+            events.push((interval.exit, operand, false));
+        }
+    }
+    events.sort_by(|a, b| a.0.t.total_cmp(&b.0.t));
+
+    let mut result = vec![];
+    let mut within = [false, false];
+    let mut enter: Option<HitRecord> = None;
+    for (mut rec, operand, entering) in events {
+        let was_inside = inside(within[0], within[1]);
+        within[operand] = entering;
+        let is_inside = inside(within[0], within[1]);
+        if was_inside == is_inside {
+            continue;
+        }
+        // The normal already faces the ray; whether it is a front face
+        // depends on the result, not the operand: the surfaces carved by a
+        // difference are entered where the subtracted object is left.
+        rec.front_face = is_inside;
+        if is_inside {
+            enter = Some(rec);
+        } else if let Some(enter) = enter.take() {
+            if rec.t > enter.t {
+                result.push(Interval { enter, exit: rec });
+            }
+        }
+    }
+    return result;
+}
+
+/// Nearest boundary of `intervals` within (`t_min`, `t_max`).
+fn first_hit(intervals: Vec<Interval>, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    for interval in intervals {
+        for boundary in [interval.enter, interval.exit] {
+            if boundary.t >= t_max {
+                return false;
+            }
+            if boundary.t > t_min {
+                *rec = boundary;
+                return true;
+            }
+        }
+    }
+    return false;
+}
+
+fn check_closed(left: &dyn Hittable, right: &dyn Hittable) {
+    assert!(
+        left.is_closed() && right.is_closed(),
+        "CSG operands must be closed objects"
+    );
+}
+
+/// Everything inside either operand.
+pub struct CsgUnion {
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+}
+
+impl CsgUnion {
+    /// Panics unless both operands are closed.
+    pub fn new<A: Hittable + 'static, B: Hittable + 'static>(left: A, right: B) -> Self {
+        Self::shared(Arc::new(left), Arc::new(right))
+    }
+
+    pub fn shared(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        check_closed(left.as_ref(), right.as_ref());
+        CsgUnion { left, right }
+    }
+}
+
+impl Hittable for CsgUnion {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        first_hit(self.intervals(r, rng), t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(
+            self.left
+                .bounding_box()?
+                .surrounding(&self.right.bounding_box()?),
+        )
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        combine(
+            self.left.intervals(r, rng),
+            self.right.intervals(r, rng),
+            |a, b| a || b,
+        )
+    }
+}
+
+/// Everything inside both operands.
+pub struct CsgIntersection {
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+}
+
+impl CsgIntersection {
+    /// Panics unless both operands are closed.
+    pub fn new<A: Hittable + 'static, B: Hittable + 'static>(left: A, right: B) -> Self {
+        Self::shared(Arc::new(left), Arc::new(right))
+    }
+
+    pub fn shared(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        check_closed(left.as_ref(), right.as_ref());
+        CsgIntersection { left, right }
+    }
+}
+
+impl Hittable for CsgIntersection {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        first_hit(self.intervals(r, rng), t_min, t_max, rec)
+    }
+
+    /// Overlap of the operands' boxes. If only one of them is bounded, its
+    /// box alone holds the intersection.
+    fn bounding_box(&self) -> Option<Aabb> {
+        match (self.left.bounding_box(), self.right.bounding_box()) {
+            (Some(a), Some(b)) => {
+                let min = a.min().max(b.min());
+                // Disjoint boxes leave an empty object; keep the box valid.
+                let max = a.max().min(b.max()).max(&min);
+                Some(Aabb::new(min, max))
+            }
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        let left = self.left.intervals(r, rng);
+        if left.is_empty() {
+            return left;
+        }
+        combine(left, self.right.intervals(r, rng), |a, b| a && b)
+    }
+}
+
+/// Everything inside the left operand but not the right one. The carved
+/// surfaces keep the material of the right operand.
+pub struct CsgDifference {
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+}
+
+impl CsgDifference {
+    /// Panics unless both operands are closed.
+    pub fn new<A: Hittable + 'static, B: Hittable + 'static>(left: A, right: B) -> Self {
+        Self::shared(Arc::new(left), Arc::new(right))
+    }
+
+    pub fn shared(left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Self {
+        check_closed(left.as_ref(), right.as_ref());
+        CsgDifference { left, right }
+    }
+}
+
+impl Hittable for CsgDifference {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        first_hit(self.intervals(r, rng), t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.left.bounding_box()
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        let left = self.left.intervals(r, rng);
+        if left.is_empty() {
+            return left;
+        }
+        combine(left, self.right.intervals(r, rng), |a, b| a && !b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CsgDifference, CsgIntersection, CsgUnion};
+    use crate::{
+        cuboid::Cuboid,
+        cylinder::Cylinder,
+        hittable::{hit, test_rng, HitRecord, Hittable, HittableList},
+        mat4::Mat4,
+        material::{diffuse_light::DiffuseLight, lambertian::Lambertian},
+        ray::Ray,
+        sphere::Sphere,
+        transform::Transform,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn sphere(x: f64, radius: f64, red: f64) -> Sphere {
+        Sphere::new(
+            Point3::new(x, 0, 0),
+            radius,
+            DiffuseLight::new(Color::new(red, 0, 0)),
+        )
+    }
+
+    /// Red channel of the light emitted at `rec`, to tell the operands
+    /// apart.
+    fn red(rec: &HitRecord) -> f64 {
+        rec.material.emitted(rec).x()
+    }
+
+    fn along_x() -> Ray {
+        Ray::new(Point3::new(-10, 0, 0), Vec3::new(1, 0, 0))
+    }
+
+    fn bounds(object: &dyn Hittable, r: &Ray) -> Vec<(f64, f64)> {
+        object
+            .intervals(r, &mut test_rng())
+            .iter()
+            .map(|i| (i.enter.t, i.exit.t))
+            .collect()
+    }
+
+    #[test]
+    fn intervals_of_primitives() {
+        let r = along_x();
+        assert_eq!(bounds(&sphere(0.0, 1.0, 1.0), &r), [(9.0, 11.0)]);
+        let cuboid = Cuboid::new(
+            Point3::new(-1, -1, -1),
+            Point3::new(1, 1, 1),
+            Lambertian::default(),
+        );
+        assert_eq!(bounds(&cuboid, &r), [(9.0, 11.0)]);
+        let intervals = cuboid.intervals(&r, &mut test_rng());
+        assert!(intervals[0].enter.front_face && !intervals[0].exit.front_face);
+        // Rays starting inside still see where they entered.
+        let inside = Ray::new(Point3::new(0, 0, 0), Vec3::new(1, 0, 0));
+        assert_eq!(bounds(&cuboid, &inside), [(-1.0, 1.0)]);
+        // Open shapes have no inside.
+        let tube = Cylinder::new(1.0, 2.0, false, Lambertian::default());
+        assert!(!tube.is_closed());
+        assert!(bounds(&tube, &Ray::new(Point3::new(-5, 1, 0), Vec3::new(1, 0, 0))).is_empty());
+        assert!(!HittableList::new().is_closed());
+    }
+
+    #[test]
+    fn union() {
+        let union = CsgUnion::new(sphere(-1.0, 1.5, 1.0), sphere(1.0, 1.5, 0.5));
+        assert_eq!(bounds(&union, &along_x()), [(7.5, 12.5)]);
+        let rec = hit(&union, &along_x()).unwrap();
+        assert_eq!(rec.t, 7.5);
+        assert_eq!(red(&rec), 1.0);
+        // The overlap has no inner surfaces.
+        let rec = hit(&union, &Ray::new(Point3::new(0, 0, 0), Vec3::new(1, 0, 0))).unwrap();
+        assert_eq!(rec.t, 2.5);
+        assert!(!rec.front_face);
+        assert_eq!(red(&rec), 0.5);
+        // Disjoint operands keep two intervals.
+        let apart = CsgUnion::new(sphere(-3.0, 1.0, 1.0), sphere(3.0, 1.0, 0.5));
+        assert_eq!(bounds(&apart, &along_x()), [(6.0, 8.0), (12.0, 14.0)]);
+    }
+
+    #[test]
+    fn intersection() {
+        let lens = CsgIntersection::new(sphere(-1.0, 1.5, 1.0), sphere(1.0, 1.5, 0.5));
+        assert_eq!(bounds(&lens, &along_x()), [(9.5, 10.5)]);
+        let rec = hit(&lens, &along_x()).unwrap();
+        assert_eq!(rec.t, 9.5);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(-1, 0, 0));
+        assert_eq!(red(&rec), 0.5);
+        let bbox = lens.bounding_box().unwrap();
+        assert_eq!(*bbox.min(), Point3::new(-0.5, -1.5, -1.5));
+        assert_eq!(*bbox.max(), Point3::new(0.5, 1.5, 1.5));
+        let apart = CsgIntersection::new(sphere(-3.0, 1.0, 1.0), sphere(3.0, 1.0, 0.5));
+        assert!(hit(&apart, &along_x()).is_none());
+    }
+
+    #[test]
+    fn difference() {
+        // A unit cube with a spherical bite taken out of its +X face.
+        let bitten = CsgDifference::new(
+            Cuboid::new(
+                Point3::new(-1, -1, -1),
+                Point3::new(1, 1, 1),
+                DiffuseLight::new(Color::new(1, 0, 0)),
+            ),
+            sphere(1.0, 0.5, 0.5),
+        );
+        let from_right = Ray::new(Point3::new(10, 0, 0), Vec3::new(-1, 0, 0));
+        assert_eq!(bounds(&bitten, &from_right), [(9.5, 11.0)]);
+        let rec = hit(&bitten, &from_right).unwrap();
+        // The bite is entered where the ray leaves the sphere, and faces
+        // out of the remaining solid.
+        assert_eq!(rec.t, 9.5);
+        assert!(rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(1, 0, 0));
+        assert_eq!(red(&rec), 0.5);
+        // Away from the bite the face of the cube is untouched.
+        let rec = hit(
+            &bitten,
+            &Ray::new(Point3::new(10, 0.9, 0), Vec3::new(-1, 0, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 9.0);
+        assert_eq!(red(&rec), 1.0);
+
+        // Subtracting a sphere splits the interval through it.
+        let hollow = CsgDifference::new(sphere(0.0, 2.0, 1.0), sphere(0.0, 1.0, 0.5));
+        assert_eq!(bounds(&hollow, &along_x()), [(8.0, 9.0), (11.0, 12.0)]);
+    }
+
+    #[test]
+    fn nested_and_transformed() {
+        // A cylinder drilled through a rounded cube, then turned and moved.
+        let rounded = CsgIntersection::new(
+            Cuboid::new(
+                Point3::new(-1, -1, -1),
+                Point3::new(1, 1, 1),
+                Lambertian::default(),
+            ),
+            sphere(0.0, 1.3, 1.0),
+        );
+        let drill = Transform::new(
+            Cylinder::new(0.5, 4.0, true, Lambertian::default()),
+            Mat4::translation(&Vec3::new(0, -2, 0)),
+        );
+        let part = Transform::new(
+            CsgDifference::new(rounded, drill),
+            Mat4::rotation(&Vec3::new(0, 0, 1), 90.0),
+        )
+        .translate(&Vec3::new(0, 0, -5));
+        assert!(part.is_closed());
+
+        // The hole now runs along X.
+        let r = Ray::new(Point3::new(-10, 0, -5), Vec3::new(1, 0, 0));
+        assert!(hit(&part, &r).is_none());
+        let r = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1));
+        let spans = bounds(&part, &r);
+        assert_eq!(spans.len(), 2, "{:?}", spans);
+        assert!((spans[0].0 - 4.0).abs() < 1e-9 && (spans[0].1 - 4.5).abs() < 1e-9);
+        assert!((spans[1].0 - 5.5).abs() < 1e-9 && (spans[1].1 - 6.0).abs() < 1e-9);
+        let rec = hit(&part, &r).unwrap();
+        assert!((rec.normal - Vec3::new(0, 0, 1)).length() < 1e-9);
+        // Inside the hole, the next surface is its wall.
+        let r = Ray::new(Point3::new(0, 0, -5), Vec3::new(0, 0, -1));
+        let rec = hit(&part, &r).unwrap();
+        assert!((rec.t - 0.5).abs() < 1e-9);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_open_operands() {
+        CsgUnion::new(
+            sphere(0.0, 1.0, 1.0),
+            Cylinder::new(1.0, 1.0, false, Lambertian::default()),
+        );
+    }
+}
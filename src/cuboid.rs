@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{crossing_intervals, HitRecord, Hittable, HittableList, Interval},
+    material::Material,
+    quad::Quad,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Axis-aligned box made of six outward-facing quads sharing one material.
+pub struct Cuboid {
+    min: Point3,
+    max: Point3,
+    sides: HittableList<'static>,
+}
+
+impl Cuboid {
+    /// Box with opposite corners `a` and `b`.
+    pub fn new<M: Material + 'static>(a: Point3, b: Point3, material: M) -> Self {
+        let min = a.min(&b);
+        let max = a.max(&b);
+        let material: Arc<dyn Material> = Arc::new(material);
+        let d = max - min;
+        let dx = Vec3::new(d.x(), 0, 0);
+        let dy = Vec3::new(0, d.y(), 0);
+        let dz = Vec3::new(0, 0, d.z());
+
+        let mut sides = HittableList::new();
+        let m = || material.clone();
+        sides.add(Quad::new(
+            Point3::new(min.x(), min.y(), max.z()),
+            dx,
+            dy,
+            m(),
+        )); // front
+        sides.add(Quad::new(
+            Point3::new(max.x(), min.y(), max.z()),
+            -dz,
+            dy,
+            m(),
+        )); // right
+        sides.add(Quad::new(
+            Point3::new(max.x(), min.y(), min.z()),
+            -dx,
+            dy,
+            m(),
+        )); // back
+        sides.add(Quad::new(
+            Point3::new(min.x(), min.y(), min.z()),
+            dz,
+            dy,
+            m(),
+        )); // left
+        sides.add(Quad::new(
+            Point3::new(min.x(), max.y(), max.z()),
+            dx,
+            -dz,
+            m(),
+        )); // top
+        sides.add(Quad::new(
+            Point3::new(min.x(), min.y(), min.z()),
+            dx,
+            dz,
+            m(),
+        )); // bottom
+        Cuboid { min, max, sides }
+    }
+
+    pub fn min(&self) -> &Point3 {
+        &self.min
+    }
+
+    pub fn max(&self) -> &Point3 {
+        &self.max
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        self.sides.hit(r, t_min, t_max, rec, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max))
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cuboid;
+    use crate::{
+        aabb::Aabb,
+        hittable::{test_rng, HitRecord, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn every_face_points_outwards() {
+        let cuboid = Cuboid::new(
+            Point3::new(1, 1, 1),
+            Point3::new(-1, -1, -1),
+            Lambertian::default(),
+        );
+        for axis in [
+            Vec3::new(1, 0, 0),
+            Vec3::new(0, 1, 0),
+            Vec3::new(0, 0, 1),
+            Vec3::new(-1, 0, 0),
+            Vec3::new(0, -1, 0),
+            Vec3::new(0, 0, -1),
+        ] {
+            let r = Ray::new(axis * 5 + Vec3::new(0.1, 0.2, 0.3) * 0.5, -axis);
+            let mut rec = HitRecord::default();
+            assert!(
+                cuboid.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()),
+                "{}",
+                axis
+            );
+            assert!((rec.p.dot(&axis) - 1.0).abs() < 1e-12, "{}", axis);
+            assert_eq!(rec.normal, axis);
+            assert!(rec.front_face, "{}", axis);
+        }
+    }
+
+    #[test]
+    fn hit_from_inside() {
+        let cuboid = Cuboid::new(
+            Point3::new(0, 0, 0),
+            Point3::new(1, 2, 3),
+            Lambertian::default(),
+        );
+        let r = Ray::new(Point3::new(0.5, 1, 1.5), Vec3::new(0, 1, 0));
+        let mut rec = HitRecord::default();
+        assert!(cuboid.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 1.0);
+        assert!(!rec.front_face);
+        assert_eq!(rec.normal, Vec3::new(0, -1, 0));
+        assert_eq!(
+            cuboid.bounding_box(),
+            Some(Aabb::new(Point3::new(0, 0, 0), Point3::new(1, 2, 3)))
+        );
+    }
+}
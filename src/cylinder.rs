@@ -0,0 +1,271 @@
+use std::f64::consts::PI;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{crossing_intervals, HitRecord, Hittable, Interval},
+    material::Material,
+    plane::intersect_plane,
+    poly::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Angle of `p` around the Y axis over 2π, starting from -X like the `u` of
+/// spheres.
+pub(crate) fn axial_u(p: &Point3) -> f64 {
+    ((-p.z()).atan2(p.x()) + PI) / (2.0 * PI)
+}
+
+/// Ray parameter where `r` meets the disk of `radius` centered on the Y
+/// axis at height `y`, with the distance to the axis over the radius.
+pub(crate) fn hit_cap(r: &Ray, y: f64, radius: f64, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+    let t = intersect_plane(r, &Point3::new(0, y, 0), &Vec3::new(0, 1, 0), t_min, t_max)?;
+    let p = r.at(t);
+    let distance = (p.x() * p.x() + p.z() * p.z()).sqrt();
+    if distance > radius {
+        return None;
+    }
+    return Some((t, distance / radius));
+}
+
+/// Cylinder around the Y axis from the origin up to `height`, closed by two
+/// disks if `capped`. Place it with a `Transform`. On the side `u` goes
+/// around the axis and `v` up; caps use the polar coordinates of a `Disk`.
+pub struct Cylinder {
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Cylinder {
+    pub fn new<M: Material + 'static>(radius: f64, height: f64, capped: bool, material: M) -> Self {
+        Cylinder {
+            radius,
+            height,
+            capped,
+            material: Box::new(material),
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn capped(&self) -> bool {
+        self.capped
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let (o, d) = (r.origin(), r.direction());
+        let mut closest = t_max;
+        // Outward normal and `v` of the closest hit so far.
+        let mut found: Option<(Vec3, f64)> = None;
+
+        let a = d.x() * d.x() + d.z() * d.z();
+        let b = 2.0 * (o.x() * d.x() + o.z() * d.z());
+        let c = o.x() * o.x() + o.z() * o.z() - self.radius * self.radius;
+        // Rays parallel to the axis never cross the side.
+        if a > 0.0 {
+            for t in solve_quadratic(a, b, c) {
+                if t <= t_min || t >= closest {
+                    continue;
+                }
+                let p = r.at(t);
+                if (0.0..=self.height).contains(&p.y()) {
+                    closest = t;
+                    let normal = Vec3::new(p.x(), 0, p.z()) / self.radius;
+                    found = Some((normal, p.y() / self.height));
+                    break;
+                }
+            }
+        }
+        if self.capped {
+            for (y, normal) in [
+                (0.0, Vec3::new(0, -1, 0)),
+                (self.height, Vec3::new(0, 1, 0)),
+            ] {
+                if let Some((t, v)) = hit_cap(r, y, self.radius, t_min, closest) {
+                    closest = t;
+                    found = Some((normal, v));
+                }
+            }
+        }
+
+        let (normal, v) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+        rec.t = closest;
+        rec.p = r.at(closest);
+        rec.set_face_normal(r, &normal);
+        rec.u = axial_u(&rec.p);
+        rec.v = v;
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Point3::new(-self.radius, 0, -self.radius),
+            Point3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.capped
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cylinder;
+    use crate::{
+        hittable::{hit, Hittable},
+        mat4::Mat4,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        transform::Transform,
+        vec3::{Point3, Vec3},
+    };
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn side_and_caps() {
+        let cylinder = Cylinder::new(1.0, 2.0, true, Lambertian::default());
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(-5, 1, 0), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_close(rec.normal, Vec3::new(-1, 0, 0));
+        assert!(rec.front_face);
+        assert_eq!(rec.v, 0.5);
+        assert!((rec.u - 0.0).abs() < 1e-12 || (rec.u - 1.0).abs() < 1e-12);
+
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(0.5, 5, 0), Vec3::new(0, -1, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 3.0);
+        assert_close(rec.normal, Vec3::new(0, 1, 0));
+        assert_eq!(rec.v, 0.5);
+
+        // From inside, the far side faces away from the ray.
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(0, 1, 0), Vec3::new(0, 0, 1)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 1.0);
+        assert!(!rec.front_face);
+        assert_close(rec.normal, Vec3::new(0, 0, -1));
+    }
+
+    #[test]
+    fn uncapped_is_open() {
+        let tube = Cylinder::new(1.0, 2.0, false, Lambertian::default());
+        // Straight down the axis there is nothing to hit.
+        assert!(hit(&tube, &Ray::new(Point3::new(0, 5, 0), Vec3::new(0, -1, 0))).is_none());
+        // A slanted ray enters through the open top and hits the inside.
+        let rec = hit(&tube, &Ray::new(Point3::new(0, 3, 0), Vec3::new(1, -1, 0))).unwrap();
+        assert!((rec.t - 1.0).abs() < 1e-12);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn tangent_and_grazing_rays() {
+        let cylinder = Cylinder::new(1.0, 2.0, true, Lambertian::default());
+        // Touching the side along a line of the surface.
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(-5, 1, 1), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 5.0);
+        // Perpendicular to the ray, so its side is arbitrary.
+        assert!((rec.normal.dot(&Vec3::new(0, 0, 1)).abs() - 1.0).abs() < 1e-12);
+        // Just inside the silhouette the hit is still on the surface.
+        let z = 1.0 - 1e-9;
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(-5, 1, z), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert!((rec.p.x() * rec.p.x() + rec.p.z() * rec.p.z() - 1.0).abs() < 1e-9);
+        assert!(rec.p.x().abs() < 1e-3);
+        assert!(hit(
+            &cylinder,
+            &Ray::new(Point3::new(-5, 1, 1.0 + 1e-9), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+        // Running along the side, parallel to the axis: only the rim of the
+        // cap can be hit.
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(1, 5, 0), Vec3::new(0, -1, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 3.0);
+        assert_close(rec.normal, Vec3::new(0, 1, 0));
+        let tube = Cylinder::new(1.0, 2.0, false, Lambertian::default());
+        assert!(hit(&tube, &Ray::new(Point3::new(1, 5, 0), Vec3::new(0, -1, 0))).is_none());
+        // Grazing the top cap edge-on.
+        assert!(hit(
+            &tube,
+            &Ray::new(Point3::new(-5, 2.0 + 1e-9, 0), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn placed_with_transform() {
+        // Lying along X, centered on the origin.
+        let cylinder = Transform::new(
+            Cylinder::new(0.5, 4.0, true, Lambertian::default()),
+            Mat4::translation(&Vec3::new(0, -2, 0)),
+        )
+        .rotate(&Vec3::new(0, 0, 1), -90.0);
+        let bbox = cylinder.bounding_box().unwrap();
+        assert_close(*bbox.min(), Point3::new(-2, -0.5, -0.5));
+        assert_close(*bbox.max(), Point3::new(2, 0.5, 0.5));
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(1.5, 0, 5), Vec3::new(0, 0, -1)),
+        )
+        .unwrap();
+        assert!((rec.t - 4.5).abs() < 1e-9);
+        assert_close(rec.normal, Vec3::new(0, 0, 1));
+        let rec = hit(
+            &cylinder,
+            &Ray::new(Point3::new(5, 0, 0), Vec3::new(-1, 0, 0)),
+        )
+        .unwrap();
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        assert_close(rec.normal, Vec3::new(1, 0, 0));
+    }
+}
@@ -0,0 +1,150 @@
+use std::f64::consts::PI;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::{Aabb, BBOX_PADDING},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    onb::Onb,
+    plane::intersect_plane,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Flat disk facing `normal`. Its texture coordinates are polar: `u` is the
+/// angle around the center over 2π, `v` the distance to it over the radius.
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: f64,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Disk {
+    pub fn new<M: Material + 'static>(
+        center: Point3,
+        normal: Vec3,
+        radius: f64,
+        material: M,
+    ) -> Self {
+        let normal = normal.unit();
+        let frame = Onb::new(&normal);
+        Disk {
+            center,
+            normal,
+            radius,
+            tangent: frame.u(),
+            bitangent: frame.v(),
+            material: Box::new(material),
+        }
+    }
+
+    pub fn center(&self) -> &Point3 {
+        &self.center
+    }
+
+    pub fn normal(&self) -> &Vec3 {
+        &self.normal
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let t = match intersect_plane(r, &self.center, &self.normal, t_min, t_max) {
+            Some(t) => t,
+            None => return false,
+        };
+        let p = r.at(t);
+        let d = p - self.center;
+        let distance = d.length();
+        if distance > self.radius {
+            return false;
+        }
+        rec.t = t;
+        rec.p = p;
+        rec.set_face_normal(r, &self.normal);
+        let angle = d.dot(&self.bitangent).atan2(d.dot(&self.tangent));
+        rec.u = (angle + PI) / (2.0 * PI);
+        rec.v = distance / self.radius;
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Along each axis the disk reaches radius * sin of the angle
+        // between that axis and the normal.
+        let n = &self.normal;
+        let extent = |c: f64| self.radius * (1.0 - c * c).max(0.0).sqrt();
+        let half = Vec3::new(extent(n.x()), extent(n.y()), extent(n.z()));
+        Some(Aabb::new(self.center - half, self.center + half).padded(BBOX_PADDING))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Disk;
+    use crate::{
+        hittable::{test_rng, HitRecord, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn hit_inside_radius_only() {
+        let disk = Disk::new(
+            Point3::new(0, 0, -2),
+            Vec3::new(0, 0, 1),
+            1.0,
+            Lambertian::default(),
+        );
+        let mut rec = HitRecord::default();
+        let r = Ray::new(Point3::new(0.5, 0, 0), Vec3::new(0, 0, -1));
+        assert!(disk.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.normal, Vec3::new(0, 0, 1));
+        assert!(rec.front_face);
+        assert_eq!(rec.v, 0.5);
+        assert!((0.0..=1.0).contains(&rec.u));
+
+        let outside = Ray::new(Point3::new(0.8, 0.8, 0), Vec3::new(0, 0, -1));
+        assert!(!disk.hit(&outside, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+    }
+
+    #[test]
+    fn bounding_box_of_tilted_disk() {
+        let disk = Disk::new(
+            Point3::new(0, 0, 0),
+            Vec3::new(1, 1, 0),
+            2.0,
+            Lambertian::default(),
+        );
+        let bbox = disk.bounding_box().unwrap();
+        let s = 2.0_f64.sqrt();
+        assert!((bbox.max().x() - s).abs() < 1e-9 && (bbox.max().y() - s).abs() < 1e-9);
+        assert!((bbox.max().z() - 2.0).abs() < 1e-9);
+
+        let flat = Disk::new(
+            Point3::new(0, 1, 0),
+            Vec3::new(0, -1, 0),
+            1.0,
+            Lambertian::default(),
+        );
+        let bbox = flat.bounding_box().unwrap();
+        assert!(bbox.max().y() > bbox.min().y());
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    fmt, fs,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use image::{codecs::hdr::HdrEncoder, ImageFormat, Rgb, Rgb32FImage, RgbImage};
+
+use crate::{tonemap::ToneMap, vec3::Color};
+
+#[derive(Debug)]
+pub enum FilmError {
+    UnsupportedFormat(PathBuf),
+    Io(PathBuf, io::Error),
+    Image(PathBuf, image::ImageError),
+}
+
+impl fmt::Display for FilmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilmError::UnsupportedFormat(path) => {
+                write!(f, "{}: unsupported output format", path.display())
+            }
+            FilmError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            FilmError::Image(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for FilmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FilmError::UnsupportedFormat(_) => None,
+            FilmError::Io(_, e) => Some(e),
+            FilmError::Image(_, e) => Some(e),
+        }
+    }
+}
+
+/// File format of the rendered image, chosen by the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 8-bit image written through the `image` crate (PNG, JPEG, ...).
+    Ldr(ImageFormat),
+    /// OpenEXR with 32-bit float channels.
+    Exr,
+    /// Radiance RGBE.
+    Hdr,
+    /// Portable float map.
+    Pfm,
+}
+
+impl OutputFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<OutputFormat, FilmError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("exr") => return Ok(OutputFormat::Exr),
+            Some("hdr") => return Ok(OutputFormat::Hdr),
+            Some("pfm") => return Ok(OutputFormat::Pfm),
+            _ => {}
+        }
+        match ImageFormat::from_path(path) {
+            Ok(format) if format.can_write() => Ok(OutputFormat::Ldr(format)),
+            _ => Err(FilmError::UnsupportedFormat(path.to_owned())),
+        }
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        !matches!(self, OutputFormat::Ldr(_))
+    }
+}
+
+/// Floating-point image holding the linear radiance of every pixel, with
+/// row 0 at the top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Film {
+    /// Panics if the image has more pixels than fit in memory.
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .unwrap_or_else(|| panic!("a {}x{} image is too large", width, height));
+        Film {
+            width,
+            height,
+            pixels: vec![Color::default(); len],
+        }
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        let i = self.index(x, y);
+        self.pixels[i] = color;
+    }
+
+    /// 8-bit sRGB image of the film as seen through `tone_map`.
+    pub fn to_rgb_image(&self, tone_map: &ToneMap) -> RgbImage {
+        let n0999 = 1.0 - f64::EPSILON;
+        RgbImage::from_fn(self.width, self.height, |x, y| {
+            let c = tone_map.apply(&self.get(x, y));
+            Color::new(c.x().min(n0999), c.y().min(n0999), c.z().min(n0999)).to_rgb()
+        })
+    }
+
+    fn to_rgb32f(&self) -> Vec<Rgb<f32>> {
+        self.pixels
+            .iter()
+            .map(|c| Rgb([c.x() as f32, c.y() as f32, c.z() as f32]))
+            .collect()
+    }
+
+    /// Writes the film to `path` in the format picked by its extension. The
+    /// float formats keep the linear radiance untouched; `tone_map` only
+    /// applies to 8-bit images.
+    pub fn save<P: AsRef<Path>>(&self, path: P, tone_map: &ToneMap) -> Result<(), FilmError> {
+        let path = path.as_ref();
+        let io_error = |e| FilmError::Io(path.to_owned(), e);
+        let image_error = |e| FilmError::Image(path.to_owned(), e);
+        match OutputFormat::from_path(path)? {
+            OutputFormat::Ldr(format) => self
+                .to_rgb_image(tone_map)
+                .save_with_format(path, format)
+                .map_err(image_error),
+            OutputFormat::Exr => {
+                let data = self.to_rgb32f().iter().flat_map(|p| p.0).collect();
+                Rgb32FImage::from_raw(self.width, self.height, data)
+                    .expect("film size matches its pixels")
+                    .save_with_format(path, ImageFormat::OpenExr)
+                    .map_err(image_error)
+            }
+            OutputFormat::Hdr => {
+                let file = BufWriter::new(fs::File::create(path).map_err(io_error)?);
+                HdrEncoder::new(file)
+                    .encode(&self.to_rgb32f(), self.width as usize, self.height as usize)
+                    .map_err(image_error)
+            }
+            OutputFormat::Pfm => {
+                let mut file = BufWriter::new(fs::File::create(path).map_err(io_error)?);
+                self.write_pfm(&mut file).map_err(io_error)
+            }
+        }
+    }
+
+    /// PFM stores rows bottom to top; a negative scale marks little-endian
+    /// floats.
+    fn write_pfm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "PF\n{} {}\n-1.0\n", self.width, self.height)?;
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let c = self.get(x, y);
+                for channel in [c.x(), c.y(), c.z()] {
+                    w.write_all(&(channel as f32).to_le_bytes())?;
+                }
+            }
+        }
+        w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use image::codecs::hdr::HdrDecoder;
+
+    use super::{Film, OutputFormat};
+    use crate::{
+        tonemap::{Operator, ToneMap},
+        vec3::Color,
+    };
+
+    fn film() -> Film {
+        let mut film = Film::new(3, 2);
+        film.set(0, 0, Color::new(0.25, 1.0, 0.0));
+        film.set(2, 1, Color::new(16.0, 0.5, 2.0));
+        film
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("raytracer-film-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(OutputFormat::from_path("a.exr").unwrap(), OutputFormat::Exr);
+        assert_eq!(OutputFormat::from_path("a.HDR").unwrap(), OutputFormat::Hdr);
+        assert_eq!(OutputFormat::from_path("a.pfm").unwrap(), OutputFormat::Pfm);
+        assert_eq!(
+            OutputFormat::from_path("a.png").unwrap(),
+            OutputFormat::Ldr(image::ImageFormat::Png)
+        );
+        assert!(OutputFormat::from_path("a.xyz").is_err());
+        assert!(OutputFormat::from_path("a").is_err());
+    }
+
+    #[test]
+    fn rgb_image_is_srgb_encoded_and_clamped() {
+        let img = film().to_rgb_image(&ToneMap::default());
+        assert_eq!(*img.get_pixel(0, 0), image::Rgb([137, 255, 0]));
+        assert_eq!(*img.get_pixel(2, 1), image::Rgb([255, 188, 255]));
+    }
+
+    #[test]
+    fn rgb_image_is_tone_mapped() {
+        let tone_map = ToneMap {
+            operator: Operator::Reinhard,
+            exposure: 1.0,
+            ..ToneMap::default()
+        };
+        let img = film().to_rgb_image(&tone_map);
+        // 0.25 is exposed to 0.5 and mapped to 1/3.
+        assert_eq!(img.get_pixel(0, 0).0[0], 156);
+        assert!(img.get_pixel(2, 1).0[0] < 255);
+    }
+
+    #[test]
+    fn pfm_layout() {
+        let mut bytes = vec![];
+        film().write_pfm(&mut bytes).unwrap();
+        let header = b"PF\n3 2\n-1.0\n";
+        assert_eq!(&bytes[..header.len()], header);
+        let floats: Vec<f32> = bytes[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        assert_eq!(floats.len(), 18);
+        // The bottom row comes first.
+        assert_eq!(&floats[6..9], &[16.0, 0.5, 2.0]);
+        assert_eq!(&floats[9..12], &[0.25, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn exr_keeps_radiance() {
+        let path = temp_path("test.exr");
+        film().save(&path, &ToneMap::default()).unwrap();
+        let img = image::open(&path).unwrap().into_rgb32f();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(img.get_pixel(2, 1).0, [16.0, 0.5, 2.0]);
+        assert_eq!(img.get_pixel(0, 0).0, [0.25, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn hdr_keeps_radiance() {
+        let path = temp_path("test.hdr");
+        film().save(&path, &ToneMap::default()).unwrap();
+        let file = std::io::BufReader::new(fs::File::open(&path).unwrap());
+        let pixels = HdrDecoder::new(file).unwrap().read_image_hdr().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(pixels.len(), 6);
+        // RGBE shares one exponent between the channels, so small channels
+        // lose precision next to bright ones.
+        let p = pixels[5].0;
+        assert_eq!(p[0], 16.0);
+        assert!((p[1] - 0.5).abs() < 0.1 && (p[2] - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn png_is_eight_bit() {
+        let path = temp_path("test.png");
+        film().save(&path, &ToneMap::default()).unwrap();
+        let img = image::open(&path).unwrap().into_rgb8();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(*img.get_pixel(0, 0), image::Rgb([137, 255, 0]));
+    }
+}
@@ -0,0 +1,342 @@
+use std::sync::Arc;
+
+use rand::{Rng, RngCore};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::{Material, ScatterRecord},
+    ray::Ray,
+    vec3::{Color, Point3, Vec3},
+    voxel_grid::{Channel, VoxelGrid},
+};
+
+/// Second radiation constant of Planck's law, in metre kelvin.
+const PLANCK_C2: f64 = 1.4388e-2;
+
+/// Color of a black body at `temperature` kelvin, from Planck's law at one
+/// wavelength per channel, scaled so that its largest component is one:
+/// deep red around 1000 K, white around 6500 K and blue beyond.
+pub fn blackbody(temperature: f64) -> Color {
+    if temperature <= 0.0 {
+        return Color::default();
+    }
+    // In logs, as the radiance underflows at low temperatures.
+    let log_radiance = |wavelength: f64| {
+        let x = PLANCK_C2 / (wavelength * temperature);
+        -5.0 * wavelength.ln() - (x + (-(-x).exp()).ln_1p())
+    };
+    let logs = [
+        log_radiance(610e-9),
+        log_radiance(550e-9),
+        log_radiance(465e-9),
+    ];
+    let max = logs[0].max(logs[1]).max(logs[2]);
+    return Color::new(
+        (logs[0] - max).exp(),
+        (logs[1] - max).exp(),
+        (logs[2] - max).exp(),
+    );
+}
+
+/// Phase function of a collision that also gives off light.
+#[derive(Clone)]
+struct Glow {
+    phase_function: Box<dyn Material>,
+    emit: Color,
+}
+
+impl Material for Glow {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> Option<ScatterRecord> {
+        self.phase_function.scatter(r_in, rec, rng)
+    }
+
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        self.phase_function.eval(rec, wi, wo)
+    }
+
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> f64 {
+        self.phase_function.pdf(rec, wi, wo)
+    }
+
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        self.emit
+    }
+}
+
+/// Smoke, clouds or fire whose density varies through space, given by a
+/// voxel grid stretched over an axis-aligned box. Collisions are found by
+/// delta tracking: tentative collisions are drawn as in a medium of the
+/// largest density, the majorant, and each is kept with the ratio of the
+/// density there to the majorant. The random numbers come from the
+/// renderer's RNG.
+///
+/// Grids with an emission channel glow: each collision gives off the
+/// emission there times `emission_scale`, tinted by the black-body color
+/// of the temperature channel if there is one. Only places with some
+/// density glow.
+pub struct GridMedium {
+    grid: Arc<VoxelGrid>,
+    bounds: Aabb,
+    density_scale: f64,
+    emission_scale: f64,
+    majorant: f64,
+    phase_function: Box<dyn Material + 'static>,
+}
+
+impl GridMedium {
+    /// Medium filling the box from `min` to `max`, with the densities of
+    /// the grid multiplied by `density_scale`.
+    pub fn new<M: Material + 'static>(
+        grid: VoxelGrid,
+        min: Point3,
+        max: Point3,
+        density_scale: f64,
+        phase_function: M,
+    ) -> Self {
+        Self::shared(Arc::new(grid), min, max, density_scale, phase_function)
+    }
+
+    pub fn shared<M: Material + 'static>(
+        grid: Arc<VoxelGrid>,
+        min: Point3,
+        max: Point3,
+        density_scale: f64,
+        phase_function: M,
+    ) -> Self {
+        let majorant = density_scale * grid.max(Channel::Density);
+        GridMedium {
+            grid,
+            bounds: Aabb::new(min, max),
+            density_scale,
+            emission_scale: 1.0,
+            majorant,
+            phase_function: Box::new(phase_function),
+        }
+    }
+
+    pub fn with_emission_scale(mut self, emission_scale: f64) -> Self {
+        self.emission_scale = emission_scale;
+        self
+    }
+
+    /// Largest density in the medium, which bounds the cost of tracking.
+    pub fn majorant(&self) -> f64 {
+        self.majorant
+    }
+
+    /// `p` in the unit cube of the grid.
+    fn to_grid(&self, p: &Point3) -> Point3 {
+        let (min, extent) = (self.bounds.min(), self.bounds.extent());
+        Point3::new(
+            (p.x() - min.x()) / extent.x(),
+            (p.y() - min.y()) / extent.y(),
+            (p.z() - min.z()) / extent.z(),
+        )
+    }
+
+    pub fn density(&self, p: &Point3) -> f64 {
+        self.density_scale * self.grid.sample(Channel::Density, &self.to_grid(p))
+    }
+
+    /// Light given off by a collision at `p`.
+    pub fn emission(&self, p: &Point3) -> Color {
+        if !self.grid.has(Channel::Emission) {
+            return Color::default();
+        }
+        let q = self.to_grid(p);
+        let strength = self.emission_scale * self.grid.sample(Channel::Emission, &q);
+        if !self.grid.has(Channel::Temperature) {
+            return Color::new(strength, strength, strength);
+        }
+        return blackbody(self.grid.sample(Channel::Temperature, &q)) * strength;
+    }
+}
+
+impl Hittable for GridMedium {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        if self.majorant <= 0.0 {
+            return false;
+        }
+        let (enter, exit) = match self.bounds.clip(r, f64::NEG_INFINITY, f64::INFINITY) {
+            Some(range) => range,
+            None => return false,
+        };
+        let length = r.direction().length();
+        let end = exit.min(t_max);
+        let mut t = enter.max(t_min);
+        loop {
+            t -= (1.0 - rng.gen::<f64>()).ln() / (self.majorant * length);
+            if t >= end {
+                return false;
+            }
+            let p = r.at(t);
+            if rng.gen::<f64>() * self.majorant < self.density(&p) {
+                rec.t = t;
+                rec.p = p;
+                // Media have no surface to face.
+                rec.normal = Vec3::new(1, 0, 0);
+                rec.front_face = true;
+                (rec.u, rec.v) = (0.0, 0.0);
+                let emit = self.emission(&p);
+                rec.material = if emit == Color::default() {
+                    self.phase_function.clone_box()
+                } else {
+                    Box::new(Glow {
+                        phase_function: self.phase_function.clone_box(),
+                        emit,
+                    })
+                };
+                return true;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::{blackbody, GridMedium};
+    use crate::{
+        hittable::{HitRecord, Hittable, HittableList},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+        voxel_grid::{Channel, VoxelGrid},
+    };
+
+    /// Two voxels along x, filling the box from (0, -1, -1) to (2, 1, 1).
+    fn medium(left: f32, right: f32) -> GridMedium {
+        GridMedium::new(
+            VoxelGrid::new([2, 1, 1], vec![left, right]),
+            Point3::new(0, -1, -1),
+            Point3::new(2, 1, 1),
+            1.0,
+            Lambertian::default(),
+        )
+    }
+
+    /// Fraction of `n` rays from `origin` along `direction` that collide.
+    fn collided_fraction(medium: &dyn Hittable, origin: Point3, direction: Vec3, n: usize) -> f64 {
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let mut rec = HitRecord::default();
+        let r = Ray::new(origin, direction);
+        let hits = (0..n)
+            .filter(|_| medium.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng))
+            .count();
+        return hits as f64 / n as f64;
+    }
+
+    #[test]
+    fn uniform_grid_follows_beer_lambert() {
+        let uniform = medium(0.5, 0.5);
+        assert_eq!(uniform.majorant(), 0.5);
+        let fraction = collided_fraction(&uniform, Point3::new(-1, 0, 0), Vec3::new(1, 0, 0), 4000);
+        assert!(
+            (fraction - (1.0 - (-1.0_f64).exp())).abs() < 0.02,
+            "{}",
+            fraction
+        );
+    }
+
+    #[test]
+    fn coinciding_grids_collide_independently() {
+        // Both grids fill the same box, so the pair is as dense as one grid
+        // of twice the density.
+        let mut list = HittableList::new();
+        list.add(medium(0.25, 0.25));
+        list.add(medium(0.25, 0.25));
+        let fraction = collided_fraction(&list, Point3::new(-1, 0, 0), Vec3::new(1, 0, 0), 4000);
+        assert!(
+            (fraction - (1.0 - (-1.0_f64).exp())).abs() < 0.02,
+            "{}",
+            fraction
+        );
+    }
+
+    #[test]
+    fn varying_density() {
+        // The density ramps from 0 to 1 between the voxel centers at x = 0.5
+        // and 1.5 and is flat beyond them: an optical depth of 1 along x.
+        let ramp = medium(0.0, 1.0);
+        assert_eq!(ramp.density(&Point3::new(1, 0, 0)), 0.5);
+        let fraction = collided_fraction(&ramp, Point3::new(-1, 0, 0), Vec3::new(2, 0, 0), 4000);
+        assert!(
+            (fraction - (1.0 - (-1.0_f64).exp())).abs() < 0.02,
+            "{}",
+            fraction
+        );
+        // Nothing collides where there is no density.
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let mut rec = HitRecord::default();
+        let r = Ray::new(Point3::new(-1, 0, 0), Vec3::new(1, 0, 0));
+        for _ in 0..200 {
+            if ramp.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng) {
+                assert!(rec.t > 1.5 && rec.t < 3.0, "{}", rec.t);
+            }
+        }
+        // Empty grids are never hit.
+        let empty = medium(0.0, 0.0);
+        assert!(!empty.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng));
+    }
+
+    #[test]
+    fn blackbody_colors() {
+        let ember = blackbody(1000.0);
+        assert_eq!(ember.x(), 1.0);
+        assert!(ember.y() < 0.2 && ember.z() < ember.y());
+        let daylight = blackbody(6500.0);
+        assert!(daylight.x() > 0.8 && daylight.y() > 0.8 && daylight.z() > 0.8);
+        let hot = blackbody(20000.0);
+        assert_eq!(hot.z(), 1.0);
+        assert!(hot.x() < 1.0);
+        assert_eq!(blackbody(100.0).x(), 1.0);
+        assert_eq!(blackbody(0.0), Color::default());
+    }
+
+    #[test]
+    fn fire() {
+        let grid = VoxelGrid::new([1, 1, 1], vec![1e6])
+            .with_channel(Channel::Emission, vec![2.0])
+            .with_channel(Channel::Temperature, vec![1500.0]);
+        let fire = GridMedium::new(
+            grid,
+            Point3::new(0, 0, 0),
+            Point3::new(1, 1, 1),
+            1.0,
+            Lambertian::default(),
+        )
+        .with_emission_scale(3.0);
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let mut rec = HitRecord::default();
+        let r = Ray::new(Point3::new(0.5, 0.5, 2), Vec3::new(0, 0, -1));
+        assert!(fire.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng));
+        assert!((rec.t - 1.0).abs() < 1e-4);
+        let expected = blackbody(1500.0) * 6.0;
+        assert_eq!(rec.material.emitted(&rec), expected);
+        assert_eq!(expected.x(), 6.0);
+
+        let smoke = medium(1.0, 1.0);
+        let r = Ray::new(Point3::new(-1, 0, 0), Vec3::new(1, 0, 0));
+        while !smoke.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut rng) {}
+        assert_eq!(rec.material.emitted(&rec), Color::default());
+    }
+}
@@ -1,4 +1,9 @@
+use std::sync::Arc;
+
+use rand::{Rng, RngCore};
+
 use crate::{
+    aabb::Aabb,
     material::{lambertian::Lambertian, Material},
     ray::Ray,
     vec3::{Point3, Vec3},
@@ -10,6 +15,9 @@ pub struct HitRecord {
     pub normal: Vec3,
     pub material: Box<dyn Material + 'static>,
     pub t: f64,
+    /// Surface coordinates of the hit point, for texture lookups.
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
 }
 
@@ -26,6 +34,8 @@ impl HitRecord {
             normal,
             material: Box::new(material),
             t,
+            u: 0.0,
+            v: 0.0,
             front_face,
         }
     }
@@ -52,8 +62,142 @@ impl Default for HitRecord {
     }
 }
 
+/// Stretch of a ray inside a closed object, with the hits where the ray
+/// enters and exits it.
+#[derive(Clone)]
+pub struct Interval {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
 pub trait Hittable: Sync + Send {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
+    /// Participating media draw where they scatter `r` from `rng`; surfaces
+    /// ignore it.
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool;
+    /// Box enclosing the object, or `None` if the object is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Whether the object encloses a volume, which makes its `intervals`
+    /// meaningful and lets it take part in CSG.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Every stretch of the whole line of `r`, negative parameters
+    /// included, that lies inside the object, in order. Empty for objects
+    /// that are not closed.
+    fn intervals(&self, _r: &Ray, _rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        vec![]
+    }
+
+    /// Density, per unit solid angle, with which `random` picks `direction`
+    /// from `origin`. Zero for objects that cannot be sampled, which are
+    /// never used as lights.
+    fn pdf_value(&self, _origin: &Point3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Direction from `origin` towards a random point of the object, for
+    /// sampling it as a light.
+    fn random(&self, _origin: &Point3, _rng: &mut Box<dyn RngCore>) -> Vec3 {
+        Vec3::new(1, 0, 0)
+    }
+}
+
+/// Lets several objects, e.g. transformed instances, share one object.
+impl Hittable for Arc<dyn Hittable> {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        self.as_ref().hit(r, t_min, t_max, rec, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.as_ref().bounding_box()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.as_ref().is_closed()
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        self.as_ref().intervals(r, rng)
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        self.as_ref().pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Point3, rng: &mut Box<dyn RngCore>) -> Vec3 {
+        self.as_ref().random(origin, rng)
+    }
+}
+
+/// Most crossings `crossing_intervals` looks for along one ray.
+const MAX_CROSSINGS: usize = 64;
+
+/// Intervals of a closed object found by walking its surface hits along the
+/// whole line of `r`: front faces enter the object and back faces leave it.
+pub(crate) fn crossing_intervals<H: Hittable + ?Sized>(
+    object: &H,
+    r: &Ray,
+    rng: &mut Box<dyn RngCore>,
+) -> Vec<Interval> {
+    let mut intervals = vec![];
+    if !object.is_closed() {
+        return intervals;
+    }
+    let mut enter: Option<HitRecord> = None;
+    let mut t = f64::NEG_INFINITY;
+    let mut rec = HitRecord::default();
+    for _ in 0..MAX_CROSSINGS {
+        if !object.hit(r, t, f64::INFINITY, &mut rec, rng) {
+            break;
+        }
+        t = rec.t;
+        if rec.front_face {
+            if enter.is_none() {
+                enter = Some(rec.clone());
+            }
+        } else if let Some(enter) = enter.take() {
+            // A back face with no entry before it is a tangent touch.
+            intervals.push(Interval {
+                enter,
+                exit: rec.clone(),
+            });
+        }
+    }
+    return intervals;
+}
+
+/// Seeded generator for tests.
+#[cfg(test)]
+pub(crate) fn test_rng() -> Box<dyn RngCore> {
+    use rand::SeedableRng;
+    Box::new(rand_chacha::ChaCha8Rng::seed_from_u64(0))
+}
+
+/// First hit of `r` on `object`, for tests.
+#[cfg(test)]
+pub(crate) fn hit(object: &dyn Hittable, r: &Ray) -> Option<HitRecord> {
+    let mut rec = HitRecord::default();
+    if object.hit(r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()) {
+        Some(rec)
+    } else {
+        None
+    }
 }
 
 pub struct HittableList<'a> {
@@ -71,10 +215,39 @@ impl<'a> HittableList<'a> {
     pub fn add<T: Hittable + 'a>(&mut self, t: T) {
         self.objects.push(Box::<T>::new(t))
     }
+
+    pub fn add_boxed(&mut self, t: Box<dyn Hittable + 'a>) {
+        self.objects.push(t)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable + 'a>> {
+        self.objects
+    }
+}
+
+impl<'a> Default for HittableList<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Hittable for HittableList<'a> {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
         let mut temp_rec = HitRecord::new(
             Point3::new(0, 0, 0),
             Vec3::new(0, 0, 0),
@@ -86,7 +259,7 @@ impl<'a> Hittable for HittableList<'a> {
         let mut closest_so_far = t_max;
 
         for object in &self.objects {
-            if object.hit(r, t_min, closest_so_far, &mut temp_rec) {
+            if object.hit(r, t_min, closest_so_far, &mut temp_rec, rng) {
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
                 rec.clone_from(&temp_rec);
@@ -95,4 +268,75 @@ impl<'a> Hittable for HittableList<'a> {
 
         return hit_anything;
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut objects = self.objects.iter();
+        let first = objects.next()?.bounding_box()?;
+        objects.try_fold(first, |acc, object| {
+            Some(acc.surrounding(&object.bounding_box()?))
+        })
+    }
+
+    /// Sampling a list picks one of its objects at random and samples that.
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self
+            .objects
+            .iter()
+            .map(|object| object.pdf_value(origin, direction))
+            .sum();
+        return sum / self.objects.len() as f64;
+    }
+
+    fn random(&self, origin: &Point3, rng: &mut Box<dyn RngCore>) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1, 0, 0);
+        }
+        let i = rng.gen_range(0..self.objects.len());
+        self.objects[i].random(origin, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::{Hittable, HittableList};
+    use crate::{
+        material::lambertian::Lambertian,
+        quad::Quad,
+        sphere::Sphere,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn list_samples_its_objects() {
+        let origin = Point3::new(0, 0, 0);
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let mut lights = HittableList::new();
+        assert_eq!(lights.pdf_value(&origin, &Vec3::new(0, 1, 0)), 0.0);
+        lights.add(Quad::xz(-1.0, 1.0, -1.0, 1.0, 2.0, Lambertian::default()));
+        lights.add(Sphere::new(
+            Point3::new(0, 0, -2),
+            1.0,
+            Lambertian::default(),
+        ));
+        // Half the samples go to each light.
+        let n = 4000;
+        let ahead = (0..n)
+            .filter(|_| lights.random(&origin, &mut rng).unit().z() < -0.8)
+            .count();
+        assert!((ahead as f64 / n as f64 - 0.5).abs() < 0.03);
+        assert!((lights.pdf_value(&origin, &Vec3::new(0, 5, 0)) - 0.5).abs() < 1e-12);
+        let n = 200_000;
+        let total: f64 = (0..n)
+            .map(|_| lights.pdf_value(&origin, &Vec3::random_unit_vector(&mut rng)))
+            .sum();
+        assert!((total / n as f64 * 4.0 * PI - 1.0).abs() < 0.03);
+    }
 }
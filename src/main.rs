@@ -1,108 +1,84 @@
+#![allow(
+    clippy::needless_return,
+    clippy::needless_arbitrary_self_type,
+    clippy::op_ref
+)]
+
+pub mod aabb;
+pub mod bvh;
 pub mod camera;
+pub mod cli;
+pub mod cone;
+pub mod constant_medium;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod disk;
+pub mod film;
+pub mod grid_medium;
 pub mod hittable;
+pub mod mat4;
 pub mod material;
+pub mod mesh;
+pub mod moving_sphere;
+pub mod obj;
+pub mod onb;
+pub mod paraboloid;
+pub mod plane;
+pub mod poly;
+pub mod quad;
 pub mod ray;
+pub mod render;
+pub mod scene;
+pub mod sdf;
 pub mod sphere;
+pub mod texture;
+pub mod tonemap;
+pub mod torus;
+pub mod transform;
+pub mod triangle;
 pub mod vec3;
+pub mod voxel_grid;
 
-use std::f64::INFINITY;
+use std::process;
 
-use camera::Camera;
-use hittable::{HitRecord, Hittable};
+use clap::Parser;
+use cli::Args;
 use indicatif::ProgressBar;
-use material::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
-use rand::{Rng, RngCore};
-use ray::Ray;
-use vec3::Color;
-
-use crate::{hittable::HittableList, sphere::Sphere, vec3::Point3};
-
-fn ray_color<T: Hittable>(r: Ray, world: &T, depth: usize, rng: &mut Box<dyn RngCore>) -> Color {
-    if depth <= 0 {
-        return Color::new(0, 0, 0);
-    }
-    let mut rec: HitRecord = HitRecord::default();
-    if world.hit(&r, 0.001, INFINITY, &mut rec) {
-        let material = rec.material.clone();
-        let (attenuation, scattered, flg) = material.scatter(&r, &mut rec, rng);
-        if flg {
-            return attenuation * ray_color(scattered, world, depth - 1, rng);
-        }
-        return Color::default();
-    }
-    let unit_direction = r.direction().unit();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    return (Color::new(1.0, 1.0, 1.0)) * (1.0 - t) + (Color::new(0.5, 0.7, 1.0) * t);
-}
-
-fn clamp(x: f64, min: f64, max: f64) -> f64 {
-    if x < min {
-        return min;
-    }
-    if x > max {
-        return max;
-    }
-    return x;
-}
+use scene::Scene;
 
-fn scale_color(color: Color, samples_per_pixel: u32) -> Color {
-    let scale = 1.0 / samples_per_pixel as f64;
-    let r = (color.x() * scale).sqrt();
-    let g = (color.y() * scale).sqrt();
-    let b = (color.z() * scale).sqrt();
-    let n0999 = 1.0 - f64::EPSILON;
-    Color::new(
-        clamp(r, 0.0, n0999),
-        clamp(g, 0.0, n0999),
-        clamp(b, 0.0, n0999),
-    )
+fn fail<E: std::fmt::Display>(e: E) -> ! {
+    eprintln!("error: {}", e);
+    process::exit(1);
 }
 
 fn main() {
-    let camera = Camera::new(16.0 / 9.0, 2.0, 1.0, Point3::new(0, 0, 0));
-    let image_width: u32 = 384;
-    let image_height: u32 = (image_width as f64 / camera.aspect_ratio()) as u32;
-    let mut img = image::RgbImage::new(image_width, image_height);
-    let max_depth = 50;
-
-    let mut world = HittableList::new();
-    world.add(Sphere::new(
-        Point3::new(0, 0, -1),
-        0.5,
-        Dielectric::new(1.51),
-    ));
-    world.add(Sphere::new(
-        Point3::new(0, -100.5, -1),
-        100.0,
-        Lambertian::new(Color::new(0.8, 0.8, 0.0)),
-    ));
-    world.add(Sphere::new(
-        Point3::new(1, 0, -1),
-        0.5,
-        Dielectric::new(1.51),
-    ));
-    world.add(Sphere::new(
-        Point3::new(-1, 0, -1),
-        0.5,
-        Metal::new(Color::new(0.0, 0.8, 0.0), 0.3),
-    ));
-
-    let samples_per_pixel: usize = 100;
-    let mut rng: Box<dyn RngCore> = Box::new(rand::thread_rng());
-
-    let bar = ProgressBar::new((image_width * image_height).into());
-    for (x, y, pixel) in img.enumerate_pixels_mut() {
-        let y = image_height - y;
-        let mut color = Color::new(0, 0, 0);
-        for _ in 0..samples_per_pixel {
-            let u = (x as f64 + rng.gen::<f64>()) / (image_width - 1) as f64;
-            let v = (y as f64 + rng.gen::<f64>()) / (image_height - 1) as f64;
-            let r = camera.get_ray(u, v);
-            color += ray_color(r, &world, max_depth, &mut rng);
-        }
-        *pixel = scale_color(color, samples_per_pixel as u32).to_rgb();
-        bar.inc(1)
+    let args = Args::parse();
+    args.check_output().unwrap_or_else(|e| fail(e));
+    let mut scene = Scene::load(&args.scene).unwrap_or_else(|e| fail(e));
+    args.apply(&mut scene.settings, scene.camera.aspect_ratio())
+        .unwrap_or_else(|e| fail(e));
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads as usize)
+            .build_global()
+            .unwrap_or_else(|e| fail(e));
     }
+    let settings = &scene.settings;
+
+    let bar = if args.quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(settings.image_width as u64 * settings.image_height as u64)
+    };
+    let film = render::render(
+        scene.world.as_ref(),
+        &scene.lights,
+        &scene.camera,
+        settings,
+        &bar,
+    );
     bar.finish();
-    img.save("result.png").unwrap();
+    film.save(&args.output, &args.tone_map())
+        .unwrap_or_else(|e| fail(e));
 }
@@ -0,0 +1,239 @@
+use std::ops;
+
+use crate::vec3::{Point3, Vec3};
+
+/// 4x4 matrix of an affine transform, acting on column vectors: `a * b`
+/// applies `b` first, then `a`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Mat4([[f64; 4]; 4]);
+
+impl Mat4 {
+    /// Matrix from its rows.
+    pub fn new(rows: [[f64; 4]; 4]) -> Self {
+        Mat4(rows)
+    }
+
+    pub fn identity() -> Self {
+        Self::scaling(&Vec3::new(1, 1, 1))
+    }
+
+    pub fn translation(offset: &Vec3) -> Self {
+        Mat4([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Scales each axis by the matching component of `factors`.
+    pub fn scaling(factors: &Vec3) -> Self {
+        Mat4([
+            [factors.x(), 0.0, 0.0, 0.0],
+            [0.0, factors.y(), 0.0, 0.0],
+            [0.0, 0.0, factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation by `degrees` around `axis`, looking down
+    /// the axis towards the origin.
+    pub fn rotation(axis: &Vec3, degrees: f64) -> Self {
+        let a = axis.unit();
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        Mat4([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.0,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.0,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rows(&self) -> &[[f64; 4]; 4] {
+        &self.0
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.0[j][i];
+            }
+        }
+        Mat4(m)
+    }
+
+    /// Inverse by Gauss-Jordan elimination with partial pivoting, or `None`
+    /// if the matrix is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.0;
+        let mut inv = Self::identity().0;
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))
+                .unwrap();
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+            let p = a[col][col];
+            for j in 0..4 {
+                a[col][j] /= p;
+                inv[col][j] /= p;
+            }
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+                let f = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= f * a[col][j];
+                    inv[row][j] -= f * inv[col][j];
+                }
+            }
+        }
+        return Some(Mat4(inv));
+    }
+
+    pub fn transform_point(&self, p: &Point3) -> Point3 {
+        let m = &self.0;
+        let x = m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3];
+        let y = m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3];
+        let z = m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3];
+        let w = m[3][0] * p.x() + m[3][1] * p.y() + m[3][2] * p.z() + m[3][3];
+        if w == 1.0 {
+            return Point3::new(x, y, z);
+        }
+        return Point3::new(x / w, y / w, z / w);
+    }
+
+    /// Transforms a direction, ignoring the translation.
+    pub fn transform_vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.0;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl ops::Mul for &Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.0[i][k] * rhs.0[k][j]).sum();
+            }
+        }
+        return Mat4(m);
+    }
+}
+
+impl ops::Mul for Mat4 {
+    type Output = Mat4;
+    fn mul(self, rhs: Self) -> Self::Output {
+        return &self * &rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mat4;
+    use crate::vec3::{Point3, Vec3};
+
+    fn assert_close(a: &Mat4, b: &Mat4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!(
+                    (a.rows()[i][j] - b.rows()[i][j]).abs() < 1e-12,
+                    "{:?} != {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    fn assert_close_vec(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn translation_moves_points_not_vectors() {
+        let m = Mat4::translation(&Vec3::new(1, 2, 3));
+        assert_eq!(
+            m.transform_point(&Point3::new(1, 1, 1)),
+            Point3::new(2, 3, 4)
+        );
+        assert_eq!(m.transform_vector(&Vec3::new(1, 1, 1)), Vec3::new(1, 1, 1));
+    }
+
+    #[test]
+    fn rotation() {
+        let m = Mat4::rotation(&Vec3::new(0, 0, 2), 90.0);
+        assert_close_vec(m.transform_vector(&Vec3::new(1, 0, 0)), Vec3::new(0, 1, 0));
+        let m = Mat4::rotation(&Vec3::new(1, 1, 1), 120.0);
+        assert_close_vec(m.transform_vector(&Vec3::new(1, 0, 0)), Vec3::new(0, 1, 0));
+        assert_close_vec(m.transform_vector(&Vec3::new(1, 1, 1)), Vec3::new(1, 1, 1));
+    }
+
+    #[test]
+    fn product_applies_right_operand_first() {
+        let scale = Mat4::scaling(&Vec3::new(2, 3, 4));
+        let translate = Mat4::translation(&Vec3::new(1, 0, 0));
+        let p = Point3::new(1, 1, 1);
+        assert_eq!(
+            (translate * scale).transform_point(&p),
+            Point3::new(3, 3, 4)
+        );
+        assert_eq!(
+            (scale * translate).transform_point(&p),
+            Point3::new(4, 3, 4)
+        );
+        assert_eq!(&Mat4::identity() * &scale, scale);
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Mat4::translation(&Vec3::new(1, -2, 3))
+            * Mat4::rotation(&Vec3::new(1, 2, 3), 37.0)
+            * Mat4::scaling(&Vec3::new(2, 0.5, -1));
+        let inv = m.inverse().unwrap();
+        assert_close(&(m * inv), &Mat4::identity());
+        assert_close(&(inv * m), &Mat4::identity());
+        assert!(Mat4::scaling(&Vec3::new(1, 0, 1)).inverse().is_none());
+    }
+
+    #[test]
+    fn transpose() {
+        let m = Mat4::translation(&Vec3::new(1, 2, 3));
+        assert_eq!(m.transpose().rows()[3], [1.0, 2.0, 3.0, 1.0]);
+        assert_eq!(m.transpose().transpose(), m);
+    }
+}
@@ -1,21 +1,72 @@
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
 
+use std::sync::Arc;
+
 use rand::RngCore;
 
-use crate::{hittable::HitRecord, ray::Ray, vec3::Color};
+use crate::{
+    hittable::HitRecord,
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+/// Light leaving a hit point along a sampled direction.
+pub struct ScatterRecord {
+    /// Continues the path in the sampled direction.
+    pub ray: Ray,
+    /// The BSDF for this direction times the cosine of its angle to the
+    /// normal, or the phase function of a medium, as `eval` gives it.
+    pub bsdf: Color,
+    /// Density over solid angle with which the direction was chosen.
+    pub pdf: f64,
+    /// Sampled from a distribution `eval` and `pdf` cannot describe, such
+    /// as a perfect mirror; `bsdf` is then the fraction of light carried
+    /// along the ray and `pdf` is one.
+    pub specular: bool,
+}
+
+impl ScatterRecord {
+    /// Fraction of the light arriving along the ray that leaves the hit
+    /// point towards the viewer.
+    pub fn attenuation(&self) -> Color {
+        self.bsdf / self.pdf
+    }
+}
 
+/// How a surface or medium scatters and gives off light. Directions are
+/// seen from the hit point: `wo` points back towards the viewer and `wi`
+/// towards where the light comes from.
 pub trait Material: MaterialClone + Sync + Send {
+    /// Chooses the direction the path goes on in, or `None` if the light is
+    /// absorbed.
     fn scatter(
-        self: &Self,
+        &self,
         r_in: &Ray,
-        rec: &mut HitRecord,
+        rec: &HitRecord,
         rng: &mut Box<dyn RngCore>,
-    ) -> (Color, Ray, bool)
-    where
-        Color: Sized,
-        Ray: Sized;
+    ) -> Option<ScatterRecord>;
+
+    /// BSDF times the cosine of `wi` to the normal, for light arriving from
+    /// `wi` and leaving along `wo`; black for specular materials.
+    fn eval(&self, _rec: &HitRecord, _wi: &Vec3, _wo: &Vec3) -> Color {
+        Color::default()
+    }
+
+    /// Density over solid angle with which `scatter` picks `wi` for a ray
+    /// arriving from `wo`; zero for specular materials.
+    fn pdf(&self, _rec: &HitRecord, _wi: &Vec3, _wo: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Light given off at the hit point; black for materials that do not
+    /// emit.
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
 }
 
 pub trait MaterialClone {
@@ -33,3 +84,28 @@ impl<T: 'static + Material + Clone> MaterialClone for T {
         Box::new(self.clone())
     }
 }
+
+/// Lets objects share one material instance, e.g. a named material from a
+/// scene file used by many objects.
+impl Material for Arc<dyn Material> {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> Option<ScatterRecord> {
+        self.as_ref().scatter(r_in, rec, rng)
+    }
+
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        self.as_ref().eval(rec, wi, wo)
+    }
+
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> f64 {
+        self.as_ref().pdf(rec, wi, wo)
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        self.as_ref().emitted(rec)
+    }
+}
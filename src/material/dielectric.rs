@@ -1,8 +1,8 @@
-use rand::Rng;
+use rand::{Rng, RngCore};
 
-use crate::{ray::Ray, vec3::Color};
+use crate::{hittable::HitRecord, ray::Ray, vec3::Color};
 
-use super::Material;
+use super::{Material, ScatterRecord};
 
 fn schlick(cosine: f64, ref_idx: f64) -> f64 {
     let r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
@@ -23,16 +23,11 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(
-        self: &Self,
+        &self,
         r_in: &Ray,
-        rec: &mut crate::hittable::HitRecord,
-        rng: &mut Box<dyn rand::RngCore>,
-    ) -> (Color, Ray, bool)
-    where
-        Color: Sized,
-        Ray: Sized,
-    {
-        let attenuation = Color::new(1.0, 1.0, 1.0);
+        rec: &HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> Option<ScatterRecord> {
         let etai_over_etat = if rec.front_face {
             1.0 / self.ref_idx
         } else {
@@ -43,23 +38,19 @@ impl Material for Dielectric {
         let cos_theta = -r_in_unit.dot(&rec.normal).min(1.0);
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
-        if etai_over_etat * sin_theta > 1.0 {
-            let reflected = r_in_unit.reflect(&rec.normal);
-            let scattered = Ray::new(rec.p, reflected);
-            return (attenuation, scattered, true);
-        }
-
-        let reflect_prob = schlick(cos_theta, etai_over_etat);
-        if rng.gen::<f64>() < reflect_prob {
-            let reflected = r_in_unit.reflect(&rec.normal);
-            let scattered = Ray::new(rec.p, reflected);
-            return (attenuation, scattered, true);
-        }
-
-        let refracted = r_in_unit.refract(&rec.normal.unit(), etai_over_etat);
-
-        let scattered = Ray::new(rec.p, refracted);
+        let direction = if etai_over_etat * sin_theta > 1.0
+            || rng.gen::<f64>() < schlick(cos_theta, etai_over_etat)
+        {
+            r_in_unit.reflect(&rec.normal)
+        } else {
+            r_in_unit.refract(&rec.normal.unit(), etai_over_etat)
+        };
 
-        (attenuation, scattered, true)
+        Some(ScatterRecord {
+            ray: Ray::with_time(rec.p, direction, r_in.time()),
+            bsdf: Color::new(1.0, 1.0, 1.0),
+            pdf: 1.0,
+            specular: true,
+        })
     }
 }
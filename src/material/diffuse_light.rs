@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{solid_color::SolidColor, Texture},
+    vec3::Color,
+};
+
+use super::{Material, ScatterRecord};
+
+/// Emits `emit` equally in every direction from both sides of the surface
+/// and does not reflect light.
+#[derive(Clone)]
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn from_texture(emit: Arc<dyn Texture>) -> Self {
+        Self { emit }
+    }
+}
+
+impl Default for DiffuseLight {
+    fn default() -> Self {
+        Self::new(Color::default())
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(
+        &self,
+        _: &Ray,
+        _: &HitRecord,
+        _: &mut Box<dyn RngCore + 'static>,
+    ) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        self.emit.value(rec.u, rec.v, &rec.p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::DiffuseLight;
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    #[test]
+    fn emits_without_scattering() {
+        let light = DiffuseLight::new(Color::new(4, 4, 4));
+        let rec = HitRecord::default();
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::new(Point3::new(0, 0, 1), Vec3::new(0, 0, -1));
+        assert!(light.scatter(&r, &rec, &mut rng).is_none());
+        assert_eq!(light.emitted(&rec), Color::new(4, 4, 4));
+    }
+}
@@ -0,0 +1,100 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use rand::RngCore;
+
+use crate::{
+    hittable::HitRecord,
+    ray::Ray,
+    texture::{solid_color::SolidColor, Texture},
+    vec3::{Color, Vec3},
+};
+
+use super::{Material, ScatterRecord};
+
+/// Phase function of a participating medium: scatters into every direction
+/// with equal probability, keeping `albedo` of the light.
+#[derive(Clone)]
+pub struct Isotropic {
+    albedo: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Default for Isotropic {
+    fn default() -> Self {
+        Self::new(Color::default())
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(
+        &self,
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut Box<dyn RngCore + 'static>,
+    ) -> Option<ScatterRecord> {
+        let direction = Vec3::random_unit_vector(rng);
+        let wo = -*r_in.direction();
+        Some(ScatterRecord {
+            bsdf: self.eval(rec, &direction, &wo),
+            pdf: self.pdf(rec, &direction, &wo),
+            ray: Ray::with_time(rec.p, direction, r_in.time()),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, rec: &HitRecord, _wi: &Vec3, _wo: &Vec3) -> Color {
+        self.albedo.value(rec.u, rec.v, &rec.p) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _rec: &HitRecord, _wi: &Vec3, _wo: &Vec3) -> f64 {
+        Vec3::unit_vector_pdf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::Isotropic;
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    #[test]
+    fn scatters_in_every_direction() {
+        let material = Isotropic::new(Color::new(0.5, 0.6, 0.7));
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::with_time(Point3::new(0, 0, 1), Vec3::new(0, 0, -1), 0.25);
+        let rec = HitRecord {
+            p: Point3::new(0, 0, 0),
+            ..HitRecord::default()
+        };
+        let mut mean = Vec3::new(0, 0, 0);
+        let n = 4000;
+        for _ in 0..n {
+            let record = material.scatter(&r, &rec, &mut rng).unwrap();
+            assert!(!record.specular);
+            assert!((record.attenuation() - Color::new(0.5, 0.6, 0.7)).length() < 1e-12);
+            let scattered = record.ray;
+            assert_eq!(*scattered.origin(), rec.p);
+            assert_eq!(scattered.time(), 0.25);
+            assert!((scattered.direction().length() - 1.0).abs() < 1e-9);
+            mean += *scattered.direction() / n as f64;
+        }
+        // No preferred direction, in particular not the incoming one.
+        assert!(mean.length() < 0.05, "{}", mean);
+    }
+}
@@ -1,34 +1,131 @@
+use std::{f64::consts::PI, sync::Arc};
+
 use rand::RngCore;
 
 use crate::{
     hittable::HitRecord,
+    onb::Onb,
     ray::Ray,
+    texture::{solid_color::SolidColor, Texture},
     vec3::{Color, Vec3},
 };
 
-use super::Material;
+use super::{Material, ScatterRecord};
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
 
+impl Default for Lambertian {
+    fn default() -> Self {
+        Self::new(Color::default())
+    }
+}
+
 impl Material for Lambertian {
     fn scatter(
         &self,
-        _: &Ray,
-        rec: &mut HitRecord,
-        rng: &mut Box<(dyn RngCore + 'static)>,
-    ) -> (Color, Ray, bool) {
-        let scatter_direction = rec.normal + Vec3::random_unit_vector(rng);
-        let scattered = Ray::new(rec.p, scatter_direction);
-        let attenuation = self.albedo;
-        (attenuation, scattered, true)
+        r_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut Box<dyn RngCore + 'static>,
+    ) -> Option<ScatterRecord> {
+        let direction = Onb::new(&rec.normal).to_world(&Vec3::random_cosine_direction(rng));
+        let wo = -*r_in.direction();
+        Some(ScatterRecord {
+            bsdf: self.eval(rec, &direction, &wo),
+            pdf: self.pdf(rec, &direction, &wo),
+            ray: Ray::with_time(rec.p, direction, r_in.time()),
+            specular: false,
+        })
+    }
+
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, _wo: &Vec3) -> Color {
+        let cosine = wi.unit().dot(&rec.normal).max(0.0);
+        self.albedo.value(rec.u, rec.v, &rec.p) * (cosine / PI)
+    }
+
+    /// Cosine-weighted over the hemisphere around the normal.
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, _wo: &Vec3) -> f64 {
+        Onb::new(&rec.normal).to_local(wi).cosine_direction_pdf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::Lambertian;
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        texture::{checker::Checker, solid_color::SolidColor},
+        vec3::{Color, Point3, Vec3},
+    };
+
+    #[test]
+    fn albedo_comes_from_the_texture() {
+        let white = Color::new(1, 1, 1);
+        let black = Color::default();
+        let material = Lambertian::from_texture(Arc::new(Checker::new(
+            Arc::new(SolidColor::new(white)),
+            Arc::new(SolidColor::new(black)),
+            1.0,
+        )));
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::new(Point3::new(0, 0, 1), Vec3::new(0, 0, -1));
+        let mut rec = HitRecord {
+            normal: Vec3::new(0, 0, 1),
+            p: Point3::new(0.5, 0.5, 0.5),
+            ..HitRecord::default()
+        };
+        let scattered = material.scatter(&r, &rec, &mut rng).unwrap();
+        assert_eq!(scattered.attenuation(), white);
+        rec.p = Point3::new(1.5, 0.5, 0.5);
+        let scattered = material.scatter(&r, &rec, &mut rng).unwrap();
+        assert_eq!(scattered.attenuation(), black);
+    }
+
+    #[test]
+    fn samples_the_cosine_lobe() {
+        let material = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::new(Point3::new(0, 0, 1), Vec3::new(0, 0, -1));
+        let rec = HitRecord {
+            normal: Vec3::new(0, 0, 1),
+            ..HitRecord::default()
+        };
+        let wo = Vec3::new(0, 0, 1);
+        let n = 20_000;
+        let mut mean_cosine = 0.0;
+        for _ in 0..n {
+            let scattered = material.scatter(&r, &rec, &mut rng).unwrap();
+            let wi = *scattered.ray.direction();
+            assert!(!scattered.specular);
+            assert_eq!(scattered.pdf, material.pdf(&rec, &wi, &wo));
+            assert_eq!(scattered.bsdf, material.eval(&rec, &wi, &wo));
+            mean_cosine += wi.unit().z() / n as f64;
+        }
+        // The mean cosine of a cosine-weighted hemisphere is 2/3.
+        assert!((mean_cosine - 2.0 / 3.0).abs() < 0.01, "{}", mean_cosine);
+        let below = Vec3::new(0, 1, -1);
+        assert_eq!(material.pdf(&rec, &below, &wo), 0.0);
+        assert_eq!(material.eval(&rec, &below, &wo), Color::default());
+        let straight_up = material.eval(&rec, &Vec3::new(0, 0, 2), &wo);
+        assert!((straight_up.x() - 0.5 / PI).abs() < 1e-12);
     }
 }
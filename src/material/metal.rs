@@ -1,39 +1,184 @@
+use std::{f64::consts::PI, sync::Arc};
+
 use rand::RngCore;
 
 use crate::{
     hittable::HitRecord,
     ray::Ray,
+    texture::{solid_color::SolidColor, Texture},
     vec3::{Color, Vec3},
 };
 
-use super::Material;
+use super::{Material, ScatterRecord};
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Metal {
-    albedo: Color,
-    fuzz: f64,
+    albedo: Arc<dyn Texture>,
+    fuzz: Arc<dyn Texture>,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
-        Self {
-            albedo,
-            fuzz: fuzz.min(1.0),
-        }
+        let fuzz = fuzz.min(1.0);
+        Self::from_textures(
+            Arc::new(SolidColor::new(albedo)),
+            Arc::new(SolidColor::new(Color::new(fuzz, fuzz, fuzz))),
+        )
+    }
+
+    /// The fuzz at a point is the mean of the channels of the `fuzz`
+    /// texture, clamped to [0, 1].
+    pub fn from_textures(albedo: Arc<dyn Texture>, fuzz: Arc<dyn Texture>) -> Self {
+        Self { albedo, fuzz }
+    }
+
+    fn fuzz(&self, rec: &HitRecord) -> f64 {
+        let f = self.fuzz.value(rec.u, rec.v, &rec.p);
+        ((f.x() + f.y() + f.z()) / 3.0).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for Metal {
+    fn default() -> Self {
+        Self::new(Color::default(), 0.0)
     }
 }
 
 impl Material for Metal {
     fn scatter(
-        self: &Self,
+        &self,
         r_in: &Ray,
-        rec: &mut HitRecord,
-        rng: &mut Box<(dyn RngCore + 'static)>,
-    ) -> (Color, Ray, bool) {
+        rec: &HitRecord,
+        rng: &mut Box<dyn RngCore + 'static>,
+    ) -> Option<ScatterRecord> {
         let reflected = r_in.direction().unit().reflect(&rec.normal);
-        let scattered = Ray::new(rec.p, reflected + Vec3::random_unit_vector(rng) * self.fuzz);
-        let attenuation = self.albedo;
-        let flg = scattered.direction().dot(&rec.normal) > 0.0;
-        return (attenuation, scattered, flg);
+        let fuzz = self.fuzz(rec);
+        let direction = reflected + Vec3::random_unit_vector(rng) * fuzz;
+        if direction.dot(&rec.normal) <= 0.0 {
+            return None;
+        }
+        let ray = Ray::with_time(rec.p, direction, r_in.time());
+        if fuzz == 0.0 {
+            return Some(ScatterRecord {
+                ray,
+                bsdf: self.albedo.value(rec.u, rec.v, &rec.p),
+                pdf: 1.0,
+                specular: true,
+            });
+        }
+        let wo = -*r_in.direction();
+        return Some(ScatterRecord {
+            bsdf: self.eval(rec, &direction, &wo),
+            pdf: self.pdf(rec, &direction, &wo),
+            ray,
+            specular: false,
+        });
+    }
+
+    /// The albedo wherever the fuzzy reflection lands above the surface,
+    /// so that each sample keeps the albedo of the light.
+    fn eval(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> Color {
+        if wi.dot(&rec.normal) <= 0.0 {
+            return Color::default();
+        }
+        self.albedo.value(rec.u, rec.v, &rec.p) * self.pdf(rec, wi, wo)
+    }
+
+    /// Fuzzy reflections are the mirror direction plus a random point on a
+    /// sphere of radius `fuzz`. The density of `wi` sums the points where
+    /// the line along it crosses that sphere.
+    fn pdf(&self, rec: &HitRecord, wi: &Vec3, wo: &Vec3) -> f64 {
+        let fuzz = self.fuzz(rec);
+        if fuzz == 0.0 {
+            return 0.0;
+        }
+        let wi = wi.unit();
+        let reflected = (-*wo).unit().reflect(&rec.normal);
+        // The line t·wi meets the sphere where t² - 2bt + 1 - fuzz² = 0.
+        let b = wi.dot(&reflected);
+        let discriminant = b * b - (1.0 - fuzz * fuzz);
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let root = discriminant.sqrt();
+        let crossings: f64 = [b - root, b + root]
+            .iter()
+            .filter(|t| **t > 0.0)
+            .map(|t| t * t)
+            .sum();
+        return crossings / (4.0 * PI * fuzz * root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::Metal;
+    use crate::{
+        hittable::HitRecord,
+        material::Material,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn rec() -> HitRecord {
+        HitRecord {
+            normal: Vec3::new(0, 0, 1),
+            ..HitRecord::default()
+        }
+    }
+
+    #[test]
+    fn polished_metal_is_specular() {
+        let material = Metal::new(Color::new(0.9, 0.8, 0.7), 0.0);
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::new(Point3::new(-1, 0, 1), Vec3::new(1, 0, -1));
+        let scattered = material.scatter(&r, &rec(), &mut rng).unwrap();
+        assert!(scattered.specular);
+        assert_eq!(scattered.attenuation(), Color::new(0.9, 0.8, 0.7));
+        let mirror = Vec3::new(1, 0, 1).unit();
+        assert!((scattered.ray.direction().unit() - mirror).length() < 1e-12);
+        let (wi, wo) = (Vec3::new(1, 0, 1), Vec3::new(-1, 0, 1));
+        assert_eq!(material.eval(&rec(), &wi, &wo), Color::default());
+        assert_eq!(material.pdf(&rec(), &wi, &wo), 0.0);
+    }
+
+    #[test]
+    fn fuzzy_density_matches_sampling() {
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let wo = Vec3::new(-1, 0, 1);
+        for fuzz in [0.3, 1.0] {
+            let material = Metal::new(Color::new(0.5, 0.5, 0.5), fuzz);
+            // The density integrates to one over all directions.
+            let n = 200_000;
+            let total: f64 = (0..n)
+                .map(|_| material.pdf(&rec(), &Vec3::random_unit_vector(&mut rng), &wo))
+                .sum();
+            assert!(
+                (total / n as f64 * 4.0 * PI - 1.0).abs() < 0.03,
+                "{} {}",
+                fuzz,
+                total
+            );
+            let r = Ray::new(Point3::new(-1, 0, 1), -wo);
+            for _ in 0..100 {
+                if let Some(scattered) = material.scatter(&r, &rec(), &mut rng) {
+                    assert!(!scattered.specular);
+                    assert!(scattered.pdf > 0.0);
+                    let attenuation = scattered.attenuation();
+                    assert!((attenuation.x() - 0.5).abs() < 1e-9, "{}", attenuation);
+                }
+            }
+        }
+        // With a fuzz of one the sphere passes through the hit point, and the
+        // directions follow the cosine around the mirror direction.
+        let material = Metal::new(Color::new(1, 1, 1), 1.0);
+        let wi = Vec3::new(1.0, 0.3, 1.2);
+        let cosine = wi.unit().dot(&Vec3::new(1, 0, 1).unit());
+        assert!((material.pdf(&rec(), &wi, &wo) - cosine / PI).abs() < 1e-12);
     }
 }
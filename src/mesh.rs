@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    bvh::BvhNode,
+    hittable::{HitRecord, Hittable, HittableList},
+    material::Material,
+    ray::Ray,
+    triangle,
+    vec3::{Point3, Vec3},
+};
+
+/// One triangle of a [`TriangleMesh`], as indices into the shared arrays of
+/// the mesh. Positions, normals and texture coordinates are indexed
+/// separately, like the faces of an OBJ file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+}
+
+impl MeshFace {
+    pub fn new(positions: [usize; 3]) -> Self {
+        MeshFace {
+            positions,
+            normals: None,
+            uvs: None,
+        }
+    }
+}
+
+struct MeshData {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    material: Arc<dyn Material>,
+}
+
+/// Triangle of a mesh; only holds its indices and a handle on the mesh data.
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: MeshFace,
+}
+
+impl MeshTriangle {
+    fn vertices(&self) -> [&Point3; 3] {
+        let [a, b, c] = self.face.positions;
+        let positions = &self.mesh.positions;
+        [&positions[a], &positions[b], &positions[c]]
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let [v0, v1, v2] = self.vertices();
+        let (t, b1, b2) = match triangle::intersect(r, v0, v1, v2, t_min, t_max) {
+            Some(hit) => hit,
+            None => return false,
+        };
+        rec.t = t;
+        rec.p = r.at(t);
+        let geometric = (v1 - v0).cross(&(v2 - v0)).unit();
+        rec.set_face_normal(r, &geometric);
+        if let Some([a, b, c]) = self.face.normals {
+            let normals = &self.mesh.normals;
+            let b0 = 1.0 - b1 - b2;
+            let shading = (normals[a] * b0 + normals[b] * b1 + normals[c] * b2).unit();
+            rec.normal = if rec.front_face { shading } else { -shading };
+        }
+        (rec.u, rec.v) = match self.face.uvs {
+            Some([a, b, c]) => {
+                let uvs = &self.mesh.uvs;
+                let b0 = 1.0 - b1 - b2;
+                (
+                    uvs[a].0 * b0 + uvs[b].0 * b1 + uvs[c].0 * b2,
+                    uvs[a].1 * b0 + uvs[b].1 * b1 + uvs[c].1 * b2,
+                )
+            }
+            None => (b1, b2),
+        };
+        rec.material = Box::new(self.mesh.material.clone());
+        true
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [v0, v1, v2] = self.vertices();
+        Some(triangle::bounding_box(v0, v1, v2))
+    }
+}
+
+/// Indexed triangle mesh. The vertex data and the material are stored once
+/// and shared by every face; faces are looked up through a [`BvhNode`].
+pub struct TriangleMesh {
+    mesh: Arc<MeshData>,
+    faces: usize,
+    bvh: BvhNode<'static>,
+}
+
+impl TriangleMesh {
+    /// Panics if the mesh has no faces or a face indexes past the end of
+    /// one of the arrays.
+    pub fn new(
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        faces: Vec<MeshFace>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        for face in &faces {
+            assert!(
+                face.positions.iter().all(|i| *i < positions.len()),
+                "mesh face {:?} indexes past the {} positions",
+                face.positions,
+                positions.len()
+            );
+            if let Some(n) = face.normals {
+                assert!(
+                    n.iter().all(|i| *i < normals.len()),
+                    "mesh face normals {:?} index past the {} normals",
+                    n,
+                    normals.len()
+                );
+            }
+            if let Some(uv) = face.uvs {
+                assert!(
+                    uv.iter().all(|i| *i < uvs.len()),
+                    "mesh face uvs {:?} index past the {} uvs",
+                    uv,
+                    uvs.len()
+                );
+            }
+        }
+        let mesh = Arc::new(MeshData {
+            positions,
+            normals,
+            uvs,
+            material,
+        });
+        let mut triangles = HittableList::new();
+        for face in &faces {
+            triangles.add(MeshTriangle {
+                mesh: mesh.clone(),
+                face: *face,
+            });
+        }
+        TriangleMesh {
+            mesh,
+            faces: faces.len(),
+            bvh: BvhNode::new(triangles),
+        }
+    }
+
+    /// Flat-shaded mesh from position indices only.
+    pub fn from_indices(
+        positions: Vec<Point3>,
+        indices: Vec<[usize; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let faces = indices.into_iter().map(MeshFace::new).collect();
+        Self::new(positions, vec![], vec![], faces, material)
+    }
+
+    pub fn positions(&self) -> &[Point3] {
+        &self.mesh.positions
+    }
+
+    pub fn normals(&self) -> &[Vec3] {
+        &self.mesh.normals
+    }
+
+    pub fn uvs(&self) -> &[(f64, f64)] {
+        &self.mesh.uvs
+    }
+
+    pub fn material(&self) -> &Arc<dyn Material> {
+        &self.mesh.material
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        self.bvh.hit(r, t_min, t_max, rec, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{MeshFace, TriangleMesh};
+    use crate::{
+        hittable::hit,
+        material::{lambertian::Lambertian, Material},
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    /// Unit square in the z = 0 plane made of two triangles.
+    fn square(normals: Vec<Vec3>) -> TriangleMesh {
+        let positions = vec![
+            Point3::new(0, 0, 0),
+            Point3::new(1, 0, 0),
+            Point3::new(1, 1, 0),
+            Point3::new(0, 1, 0),
+        ];
+        let smooth = !normals.is_empty();
+        let faces = [[0, 1, 2], [0, 2, 3]]
+            .into_iter()
+            .map(|f| MeshFace {
+                positions: f,
+                normals: if smooth { Some(f) } else { None },
+                uvs: None,
+            })
+            .collect();
+        let material: Arc<dyn Material> = Arc::new(Lambertian::default());
+        TriangleMesh::new(positions, normals, vec![], faces, material)
+    }
+
+    #[test]
+    fn flat_shading() {
+        let mesh = square(vec![]);
+        assert_eq!(mesh.face_count(), 2);
+        for (x, y) in [(0.8, 0.2), (0.2, 0.8)] {
+            let rec = hit(&mesh, &Ray::new(Point3::new(x, y, 1), Vec3::new(0, 0, -1))).unwrap();
+            assert_eq!(rec.t, 1.0);
+            assert_eq!(rec.normal, Vec3::new(0, 0, 1));
+        }
+        assert!(hit(
+            &mesh,
+            &Ray::new(Point3::new(1.5, 0.5, 1), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn smooth_shading_interpolates_normals() {
+        let tilt = |x: f64| Vec3::new(x, 0, 1).unit();
+        let mesh = square(vec![tilt(-1.0), tilt(1.0), tilt(1.0), tilt(-1.0)]);
+        let down = Vec3::new(0, 0, -1);
+
+        let middle = hit(&mesh, &Ray::new(Point3::new(0.5, 0.25, 1), down)).unwrap();
+        assert!((middle.normal - Vec3::new(0, 0, 1)).length() < 1e-9);
+
+        let right = hit(&mesh, &Ray::new(Point3::new(0.99, 0.5, 1), down)).unwrap();
+        assert!(right.normal.x() > 0.6);
+
+        let from_below = hit(&mesh, &Ray::new(Point3::new(0.99, 0.5, -1), -down)).unwrap();
+        assert!(!from_below.front_face);
+        assert!(from_below.normal.z() < 0.0);
+    }
+
+    #[test]
+    fn texture_coordinates_are_interpolated() {
+        let positions = vec![
+            Point3::new(0, 0, 0),
+            Point3::new(1, 0, 0),
+            Point3::new(1, 1, 0),
+            Point3::new(0, 1, 0),
+        ];
+        let uvs = vec![(0.0, 0.0), (0.5, 0.0), (0.5, 1.0), (0.0, 1.0)];
+        let faces = [[0, 1, 2], [0, 2, 3]]
+            .into_iter()
+            .map(|f| MeshFace {
+                positions: f,
+                normals: None,
+                uvs: Some(f),
+            })
+            .collect();
+        let material: Arc<dyn Material> = Arc::new(Lambertian::default());
+        let mesh = TriangleMesh::new(positions, vec![], uvs, faces, material);
+        let down = Vec3::new(0, 0, -1);
+        let rec = hit(&mesh, &Ray::new(Point3::new(0.5, 0.25, 1), down)).unwrap();
+        assert!((rec.u - 0.25).abs() < 1e-12 && (rec.v - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn material_is_shared_between_faces() {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::default());
+        let mesh = TriangleMesh::from_indices(
+            vec![
+                Point3::new(0, 0, 0),
+                Point3::new(1, 0, 0),
+                Point3::new(1, 1, 0),
+                Point3::new(0, 1, 0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3], [0, 1, 3], [1, 2, 3]],
+            material.clone(),
+        );
+        assert_eq!(mesh.face_count(), 4);
+        assert_eq!(Arc::strong_count(&material), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_out_of_range_indices() {
+        TriangleMesh::from_indices(
+            vec![Point3::new(0, 0, 0), Point3::new(1, 0, 0)],
+            vec![[0, 1, 2]],
+            Arc::new(Lambertian::default()),
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{crossing_intervals, HitRecord, Hittable, Interval},
+    material::Material,
+    ray::Ray,
+    sphere::hit_sphere,
+    vec3::{Point3, Vec3},
+};
+
+/// Sphere moving in a straight line from `center0` at `time0` to `center1`
+/// at `time1`, and resting at those ends before and after.
+pub struct MovingSphere {
+    center0: Point3,
+    center1: Point3,
+    time0: f64,
+    time1: f64,
+    radius: f64,
+    material: Box<dyn Material + 'static>,
+}
+
+impl MovingSphere {
+    pub fn new<M: Material + 'static>(
+        center0: Point3,
+        center1: Point3,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: M,
+    ) -> MovingSphere {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material: Box::new(material),
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        let s = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        return self.center0 + (self.center1 - self.center0) * s;
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let center = self.center(r.time());
+        if hit_sphere(&center, self.radius, r, t_min, t_max, rec) {
+            rec.material = self.material.clone_box();
+            return true;
+        }
+        return false;
+    }
+
+    /// Covers the sphere at every time, as it only moves between its two
+    /// ends.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius.abs();
+        let half = Vec3::new(r, r, r);
+        let box0 = Aabb::new(self.center0 - half, self.center0 + half);
+        let box1 = Aabb::new(self.center1 - half, self.center1 + half);
+        Some(box0.surrounding(&box1))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.radius > 0.0
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::MovingSphere;
+    use crate::{
+        bvh::BvhNode,
+        camera::Camera,
+        hittable::{test_rng, HitRecord, Hittable, HittableList},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        sphere::Sphere,
+        vec3::{Point3, Vec3},
+    };
+
+    fn sphere() -> MovingSphere {
+        MovingSphere::new(
+            Point3::new(0, 0, -5),
+            Point3::new(4, 0, -5),
+            0.0,
+            1.0,
+            1.0,
+            Lambertian::default(),
+        )
+    }
+
+    #[test]
+    fn center_moves_linearly() {
+        let s = sphere();
+        assert_eq!(s.center(0.0), Point3::new(0, 0, -5));
+        assert_eq!(s.center(0.5), Point3::new(2, 0, -5));
+        assert_eq!(s.center(1.0), Point3::new(4, 0, -5));
+        assert_eq!(s.center(-1.0), Point3::new(0, 0, -5));
+        assert_eq!(s.center(3.0), Point3::new(4, 0, -5));
+    }
+
+    #[test]
+    fn bounding_box_covers_the_motion() {
+        let bbox = sphere().bounding_box().unwrap();
+        assert_eq!(*bbox.min(), Point3::new(-1, -1, -6));
+        assert_eq!(*bbox.max(), Point3::new(5, 1, -4));
+    }
+
+    #[test]
+    fn hit_depends_on_ray_time() {
+        // Keep the moving sphere inside a BVH, next to a static sphere.
+        let mut list = HittableList::new();
+        list.add(sphere());
+        list.add(Sphere::new(
+            Point3::new(-10, 0, -5),
+            1.0,
+            Lambertian::default(),
+        ));
+        let world = BvhNode::new(list);
+        let dir = Vec3::new(0, 0, -1);
+        let mut rec = HitRecord::default();
+        for (x, time, expected) in [
+            (0.0, 0.0, true),
+            (4.0, 0.0, false),
+            (4.0, 1.0, true),
+            (2.0, 0.5, true),
+            (0.0, 1.0, false),
+        ] {
+            let r = Ray::with_time(Point3::new(x, 0, 0), dir, time);
+            assert_eq!(
+                world.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()),
+                expected,
+                "x = {}, time = {}",
+                x,
+                time
+            );
+        }
+        assert_eq!(rec.t, 4.0);
+    }
+
+    #[test]
+    fn shutter_beyond_the_motion() {
+        // A shutter open after time1 sees the sphere resting at center1,
+        // inside the box the BVH knows about.
+        let mut list = HittableList::new();
+        list.add(sphere());
+        list.add(Sphere::new(
+            Point3::new(-10, 0, -5),
+            1.0,
+            Lambertian::default(),
+        ));
+        let world = BvhNode::new(list);
+        let camera = Camera::default().with_shutter(1.5, 2.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut rec = HitRecord::default();
+        for _ in 0..20 {
+            let time = camera.get_ray(0.5, 0.5, &mut rng).time();
+            assert!(time > 1.0);
+            let r = Ray::with_time(Point3::new(4, 0, 0), Vec3::new(0, 0, -1), time);
+            assert!(world.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+            assert_eq!(rec.t, 4.0);
+        }
+    }
+}
@@ -0,0 +1,659 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::SplitWhitespace,
+    sync::Arc,
+};
+
+use crate::{
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+        Material,
+    },
+    mesh::{MeshFace, TriangleMesh},
+    vec3::{Color, Point3, Vec3},
+};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ObjError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io { source, .. } => Some(source),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// Reads the numbers following a statement keyword.
+fn floats<const N: usize>(args: &mut SplitWhitespace, statement: &str) -> Result<[f64; N], String> {
+    let mut values = [0.0; N];
+    for value in values.iter_mut() {
+        let arg = args
+            .next()
+            .ok_or_else(|| format!("`{}` needs {} numbers", statement, N))?;
+        *value = arg
+            .parse()
+            .map_err(|_| format!("`{}`: invalid number `{}`", statement, arg))?;
+    }
+    Ok(values)
+}
+
+/// Values of one `newmtl` block that the renderer can make use of.
+#[derive(Debug, Clone, PartialEq)]
+struct MtlEntry {
+    kd: Color,
+    ks: Color,
+    ke: Color,
+    ns: f64,
+    ni: f64,
+    d: f64,
+}
+
+impl Default for MtlEntry {
+    fn default() -> Self {
+        MtlEntry {
+            kd: Color::new(0.8, 0.8, 0.8),
+            ks: Color::default(),
+            ke: Color::default(),
+            ns: 0.0,
+            ni: 1.5,
+            d: 1.0,
+        }
+    }
+}
+
+fn max_component(c: &Color) -> f64 {
+    c.x().max(c.y()).max(c.z())
+}
+
+impl MtlEntry {
+    /// Picks the closest material of the renderer:
+    ///
+    /// * a non-black `Ke` makes a [`DiffuseLight`],
+    /// * a dissolve `d` below one makes a [`Dielectric`] with index `Ni`,
+    /// * a specular `Ks` brighter than the diffuse `Kd` makes a [`Metal`]
+    ///   whose fuzz shrinks as the exponent `Ns` grows,
+    /// * anything else is a [`Lambertian`] with albedo `Kd`.
+    fn to_material(&self) -> Arc<dyn Material> {
+        if max_component(&self.ke) > 0.0 {
+            return Arc::new(DiffuseLight::new(self.ke));
+        }
+        if self.d < 1.0 {
+            return Arc::new(Dielectric::new(self.ni));
+        }
+        if max_component(&self.ks) > max_component(&self.kd) {
+            let fuzz = (2.0 / (self.ns.max(0.0) + 2.0)).sqrt();
+            return Arc::new(Metal::new(self.ks, fuzz));
+        }
+        Arc::new(Lambertian::new(self.kd))
+    }
+}
+
+fn parse_mtl_entries(source: &str, path: &Path) -> Result<Vec<(String, MtlEntry)>, ObjError> {
+    let mut entries: Vec<(String, MtlEntry)> = vec![];
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Parse {
+            path: path.to_owned(),
+            line: i + 1,
+            message,
+        };
+        let mut args = line.split_whitespace();
+        let statement = match args.next() {
+            Some(s) if !s.starts_with('#') => s,
+            _ => continue,
+        };
+        if statement == "newmtl" {
+            let name = args.collect::<Vec<_>>().join(" ");
+            if name.is_empty() {
+                return Err(error("`newmtl` needs a name".into()));
+            }
+            entries.push((name, MtlEntry::default()));
+            continue;
+        }
+        let entry = match entries.last_mut() {
+            Some((_, entry)) => entry,
+            None if matches!(statement, "Kd" | "Ks" | "Ke" | "Ns" | "Ni" | "d" | "Tr") => {
+                return Err(error(format!("`{}` before any `newmtl`", statement)))
+            }
+            None => continue,
+        };
+        let color = |args: &mut SplitWhitespace| {
+            floats::<3>(args, statement).map(|[r, g, b]| Color::new(r, g, b))
+        };
+        match statement {
+            "Kd" => entry.kd = color(&mut args).map_err(error)?,
+            "Ks" => entry.ks = color(&mut args).map_err(error)?,
+            "Ke" => entry.ke = color(&mut args).map_err(error)?,
+            "Ns" => entry.ns = floats::<1>(&mut args, statement).map_err(error)?[0],
+            "Ni" => entry.ni = floats::<1>(&mut args, statement).map_err(error)?[0],
+            "d" => entry.d = floats::<1>(&mut args, statement).map_err(error)?[0],
+            "Tr" => entry.d = 1.0 - floats::<1>(&mut args, statement).map_err(error)?[0],
+            // Ambient colors, illumination models and texture maps have no
+            // counterpart in the renderer.
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// Parses an MTL library into the renderer's materials, keyed by name.
+pub fn parse_mtl(
+    source: &str,
+    path: &Path,
+) -> Result<BTreeMap<String, Arc<dyn Material>>, ObjError> {
+    Ok(parse_mtl_entries(source, path)?
+        .into_iter()
+        .map(|(name, entry)| (name, entry.to_material()))
+        .collect())
+}
+
+/// Triangles of one group that share a material.
+pub struct ObjMesh {
+    pub group: String,
+    pub material: String,
+    pub mesh: TriangleMesh,
+}
+
+/// Vertex of a face, as 0-based indices into the file-wide arrays.
+#[derive(Debug, Clone, Copy)]
+struct FaceVertex {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Resolves a 1-based or negative (relative to the end) OBJ index.
+fn resolve(index: &str, count: usize, what: &str) -> Result<usize, String> {
+    let i: i64 = index
+        .parse()
+        .map_err(|_| format!("invalid {} index `{}`", what, index))?;
+    let resolved = if i > 0 {
+        i - 1
+    } else if i < 0 {
+        count as i64 + i
+    } else {
+        return Err(format!("{} index must not be 0", what));
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!(
+            "{} index {} is out of range, there are {} so far",
+            what, i, count
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+fn parse_face_vertex(
+    arg: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<FaceVertex, String> {
+    let mut parts = arg.split('/');
+    let position = resolve(parts.next().unwrap_or(""), positions, "vertex")?;
+    let uv = match parts.next() {
+        Some("") | None => None,
+        Some(i) => Some(resolve(i, uvs, "texture coordinate")?),
+    };
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(i) => Some(resolve(i, normals, "normal")?),
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face vertex `{}`", arg));
+    }
+    Ok(FaceVertex {
+        position,
+        uv,
+        normal,
+    })
+}
+
+/// Faces collected for one (group, material) pair before they become a
+/// [`TriangleMesh`] holding only the vertices it uses.
+#[derive(Default)]
+struct MeshBuilder {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<MeshFace>,
+    position_map: HashMap<usize, usize>,
+    normal_map: HashMap<usize, usize>,
+    uv_map: HashMap<usize, usize>,
+}
+
+fn remap<T: Copy>(map: &mut HashMap<usize, usize>, out: &mut Vec<T>, all: &[T], i: usize) -> usize {
+    *map.entry(i).or_insert_with(|| {
+        out.push(all[i]);
+        out.len() - 1
+    })
+}
+
+struct ObjData {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+}
+
+impl MeshBuilder {
+    fn add_triangle(&mut self, data: &ObjData, vertices: [FaceVertex; 3]) {
+        let positions = vertices.map(|v| {
+            remap(
+                &mut self.position_map,
+                &mut self.positions,
+                &data.positions,
+                v.position,
+            )
+        });
+        let normals = if vertices.iter().all(|v| v.normal.is_some()) {
+            Some(vertices.map(|v| {
+                remap(
+                    &mut self.normal_map,
+                    &mut self.normals,
+                    &data.normals,
+                    v.normal.unwrap(),
+                )
+            }))
+        } else {
+            None
+        };
+        let uvs = if vertices.iter().all(|v| v.uv.is_some()) {
+            Some(vertices.map(|v| remap(&mut self.uv_map, &mut self.uvs, &data.uvs, v.uv.unwrap())))
+        } else {
+            None
+        };
+        self.faces.push(MeshFace {
+            positions,
+            normals,
+            uvs,
+        });
+    }
+}
+
+/// Where the materials of an OBJ file come from.
+pub enum ObjMaterials {
+    /// Read the `mtllib` libraries; faces without `usemtl` get this material.
+    FromMtl(Arc<dyn Material>),
+    /// Skip the libraries and use this material for every face.
+    Override(Arc<dyn Material>),
+}
+
+/// Parses OBJ `source`. Material libraries named by `mtllib` are read
+/// relative to the directory of `path`.
+pub fn parse_obj(
+    source: &str,
+    path: &Path,
+    obj_materials: ObjMaterials,
+) -> Result<Vec<ObjMesh>, ObjError> {
+    let use_mtl = matches!(obj_materials, ObjMaterials::FromMtl(_));
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut data = ObjData {
+        positions: vec![],
+        normals: vec![],
+        uvs: vec![],
+    };
+    let mut materials: BTreeMap<String, Arc<dyn Material>> = BTreeMap::new();
+    let mut builders: Vec<((String, String), MeshBuilder)> = vec![];
+    let mut group = String::from("default");
+    let mut material = String::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Parse {
+            path: path.to_owned(),
+            line: i + 1,
+            message,
+        };
+        let mut args = line.split_whitespace();
+        let statement = match args.next() {
+            Some(s) if !s.starts_with('#') => s,
+            _ => continue,
+        };
+        match statement {
+            "v" => {
+                let [x, y, z] = floats::<3>(&mut args, statement).map_err(error)?;
+                data.positions.push(Point3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = floats::<3>(&mut args, statement).map_err(error)?;
+                let normal = Vec3::new(x, y, z);
+                if normal.length_squared() == 0.0 {
+                    return Err(error("`vn` has zero length".into()));
+                }
+                data.normals.push(normal.unit());
+            }
+            "vt" => {
+                let u = args
+                    .next()
+                    .ok_or_else(|| error("`vt` needs a u coordinate".into()))?;
+                let u = u
+                    .parse()
+                    .map_err(|_| error(format!("`vt`: invalid number `{}`", u)))?;
+                let v = match args.next() {
+                    Some(v) => v
+                        .parse()
+                        .map_err(|_| error(format!("`vt`: invalid number `{}`", v)))?,
+                    None => 0.0,
+                };
+                data.uvs.push((u, v));
+            }
+            "f" => {
+                let vertices = args
+                    .map(|arg| {
+                        parse_face_vertex(
+                            arg,
+                            data.positions.len(),
+                            data.uvs.len(),
+                            data.normals.len(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if vertices.len() < 3 {
+                    return Err(error(format!(
+                        "a face needs at least 3 vertices, got {}",
+                        vertices.len()
+                    )));
+                }
+                let key = (group.clone(), material.clone());
+                let builder = match builders.iter().position(|(k, _)| *k == key) {
+                    Some(b) => &mut builders[b].1,
+                    None => {
+                        builders.push((key, MeshBuilder::default()));
+                        &mut builders.last_mut().unwrap().1
+                    }
+                };
+                // Polygons are split into a fan around their first vertex.
+                for k in 1..vertices.len() - 1 {
+                    builder.add_triangle(&data, [vertices[0], vertices[k], vertices[k + 1]]);
+                }
+            }
+            "g" | "o" => {
+                let name = args.collect::<Vec<_>>().join(" ");
+                group = if name.is_empty() {
+                    String::from("default")
+                } else {
+                    name
+                };
+            }
+            "usemtl" => {
+                material = args.collect::<Vec<_>>().join(" ");
+                if use_mtl && !materials.contains_key(&material) {
+                    return Err(error(format!("unknown material `{}`", material)));
+                }
+            }
+            "mtllib" if use_mtl => {
+                for file in args {
+                    let mtl_path = dir.join(file);
+                    let source = fs::read_to_string(&mtl_path).map_err(|e| {
+                        error(format!("cannot read `{}`: {}", mtl_path.display(), e))
+                    })?;
+                    materials.extend(parse_mtl(&source, &mtl_path)?);
+                }
+            }
+            // Smoothing groups, lines, points and free-form geometry are not
+            // rendered.
+            _ => {}
+        }
+    }
+
+    Ok(builders
+        .into_iter()
+        .map(|((group, material_name), b)| {
+            let mesh_material = match &obj_materials {
+                ObjMaterials::FromMtl(default) => materials
+                    .get(&material_name)
+                    .cloned()
+                    .unwrap_or_else(|| default.clone()),
+                ObjMaterials::Override(material) => material.clone(),
+            };
+            ObjMesh {
+                group,
+                material: material_name,
+                mesh: TriangleMesh::new(b.positions, b.normals, b.uvs, b.faces, mesh_material),
+            }
+        })
+        .collect())
+}
+
+/// Loads an OBJ file together with the material libraries it references.
+pub fn load_obj<P: AsRef<Path>>(
+    path: P,
+    obj_materials: ObjMaterials,
+) -> Result<Vec<ObjMesh>, ObjError> {
+    let path = path.as_ref();
+    parse_obj(&read(path)?, path, obj_materials)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, sync::Arc};
+
+    use super::{load_obj, parse_mtl_entries, parse_obj, MtlEntry, ObjError, ObjMaterials};
+    use crate::{
+        hittable::hit,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn parse(source: &str) -> Result<Vec<super::ObjMesh>, ObjError> {
+        parse_obj(
+            source,
+            Path::new("test.obj"),
+            ObjMaterials::FromMtl(Arc::new(Lambertian::default())),
+        )
+    }
+
+    fn error_line(source: &str) -> (usize, String) {
+        match parse(source) {
+            Err(ObjError::Parse { line, message, .. }) => (line, message),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("obj should not load"),
+        }
+    }
+
+    const QUAD: &str = "
+# unit square made of one polygon
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+    #[test]
+    fn triangulates_polygons() {
+        let meshes = parse(QUAD).unwrap();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].group, "default");
+        let mesh = &meshes[0].mesh;
+        assert_eq!(mesh.face_count(), 2);
+        assert_eq!(mesh.positions().len(), 4);
+        assert_eq!(mesh.uvs().len(), 4);
+        assert_eq!(mesh.normals(), &[Vec3::new(0, 0, 1)]);
+        for (x, y) in [(0.9, 0.1), (0.1, 0.9)] {
+            let r = Ray::new(Point3::new(x, y, 1), Vec3::new(0, 0, -1));
+            assert_eq!(hit(mesh, &r).unwrap().t, 1.0);
+        }
+    }
+
+    #[test]
+    fn negative_indices() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\nv 5 5 5\nf 1 2 -2\n";
+        let meshes = parse(source).unwrap();
+        assert_eq!(meshes[0].mesh.face_count(), 2);
+        let r = Ray::new(Point3::new(0.2, 0.2, 1), Vec3::new(0, 0, -1));
+        assert!(hit(&meshes[0].mesh, &r).is_some());
+    }
+
+    #[test]
+    fn groups_split_meshes() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\ng a\nf 1 2 3\ng b\nf 1 2 4\nf 1 3 4\ng a\nf 2 3 4\n";
+        let meshes = parse(source).unwrap();
+        let counts: Vec<_> = meshes
+            .iter()
+            .map(|m| {
+                (
+                    m.group.as_str(),
+                    m.mesh.face_count(),
+                    m.mesh.positions().len(),
+                )
+            })
+            .collect();
+        assert_eq!(counts, [("a", 2, 4), ("b", 2, 4)]);
+    }
+
+    #[test]
+    fn vertex_normal_forms() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 2\nf 1//1 2//1 3//1\n";
+        let meshes = parse(source).unwrap();
+        assert_eq!(meshes[0].mesh.normals(), &[Vec3::new(0, 0, 1)]);
+        assert!(meshes[0].mesh.uvs().is_empty());
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        assert_eq!(error_line("v 0 0\n").0, 1);
+        assert_eq!(error_line("v 0 0 0\nv 1 x 0\n").0, 2);
+        let (line, message) = error_line("v 0 0 0\nv 1 0 0\nv 0 1 0\n\nf 1 2 4\n");
+        assert_eq!(line, 5);
+        assert!(message.contains("out of range"), "{}", message);
+        assert_eq!(error_line("v 0 0 0\nf 0 1 1\n").0, 2);
+        assert_eq!(error_line("v 0 0 0\nv 1 0 0\nf 1 2\n").0, 3);
+        assert_eq!(error_line("v 0 0 0\nf 1/1/1/1 1 1\n").0, 2);
+        let (line, message) = error_line("v 0 0 0\nusemtl steel\n");
+        assert_eq!(line, 2);
+        assert_eq!(message, "unknown material `steel`");
+    }
+
+    #[test]
+    fn error_names_file_and_line() {
+        let e = parse("v 0 0 0\nvn 1\n").err().unwrap();
+        assert_eq!(e.to_string(), "test.obj:2: `vn` needs 3 numbers");
+        let e = parse("v 0 0 0\nvn 0 1 0\nvn 0 0 0\n").err().unwrap();
+        assert_eq!(e.to_string(), "test.obj:3: `vn` has zero length");
+    }
+
+    #[test]
+    fn mtl_values() {
+        let source =
+            "newmtl glass\nKd 0.1 0.2 0.3\nd 0.25\nNi 1.33\nnewmtl lamp\nKe 5 5 4\nNs 10\n";
+        let entries = parse_mtl_entries(source, Path::new("test.mtl")).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, "glass");
+        assert_eq!(
+            entries[0].1,
+            MtlEntry {
+                kd: Color::new(0.1, 0.2, 0.3),
+                d: 0.25,
+                ni: 1.33,
+                ..MtlEntry::default()
+            }
+        );
+        assert_eq!(entries[1].1.ke, Color::new(5, 5, 4));
+        assert_eq!(entries[1].1.ns, 10.0);
+    }
+
+    #[test]
+    fn mtl_errors() {
+        let e = parse_mtl_entries("Kd 1 1 1\n", Path::new("test.mtl"))
+            .err()
+            .unwrap();
+        assert_eq!(e.to_string(), "test.mtl:1: `Kd` before any `newmtl`");
+        let e = parse_mtl_entries("newmtl a\n\nNs shiny\n", Path::new("test.mtl"))
+            .err()
+            .unwrap();
+        assert_eq!(e.to_string(), "test.mtl:3: `Ns`: invalid number `shiny`");
+    }
+
+    #[test]
+    fn loads_materials_next_to_obj() {
+        let dir = std::env::temp_dir().join(format!("raytracer-obj-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("box.mtl"),
+            "newmtl red\nKd 0.8 0.1 0.1\nnewmtl light\nKe 4 4 4\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("box.obj"),
+            "mtllib box.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl red\nf 1 2 3\nusemtl light\nf 3 2 1\n",
+        )
+        .unwrap();
+        let meshes = load_obj(
+            dir.join("box.obj"),
+            ObjMaterials::FromMtl(Arc::new(Lambertian::default())),
+        )
+        .unwrap();
+        let names: Vec<_> = meshes.iter().map(|m| m.material.as_str()).collect();
+        assert_eq!(names, ["red", "light"]);
+
+        let r = Ray::new(Point3::new(0.2, 0.2, 1), Vec3::new(0, 0, -1));
+        let rec = hit(&meshes[1].mesh, &r).unwrap();
+        assert_eq!(rec.material.emitted(&rec), Color::new(4, 4, 4));
+
+        let gray = Lambertian::new(Color::new(0.5, 0.5, 0.5));
+        let overridden =
+            load_obj(dir.join("box.obj"), ObjMaterials::Override(Arc::new(gray))).unwrap();
+        let rec = hit(&overridden[1].mesh, &r).unwrap();
+        assert_eq!(rec.material.emitted(&rec), Color::default());
+
+        let missing = load_obj(
+            dir.join("missing.obj"),
+            ObjMaterials::FromMtl(Arc::new(Lambertian::default())),
+        );
+        assert!(matches!(missing, Err(ObjError::Io { .. })));
+        fs::write(dir.join("lost.obj"), "v 0 0 0\nmtllib lost.mtl\n").unwrap();
+        let lost = load_obj(
+            dir.join("lost.obj"),
+            ObjMaterials::FromMtl(Arc::new(Lambertian::default())),
+        );
+        match lost {
+            Err(ObjError::Parse { line, message, .. }) => {
+                assert_eq!(line, 2);
+                assert!(message.starts_with("cannot read `"), "{}", message);
+                assert!(message.contains("lost.mtl"), "{}", message);
+            }
+            _ => panic!("expected a parse error"),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
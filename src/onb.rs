@@ -0,0 +1,87 @@
+use crate::vec3::Vec3;
+
+/// Right-handed orthonormal basis whose third axis is a given direction,
+/// such as the frame around a surface normal in which directions are
+/// sampled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    /// Basis with `w` along `n`, which need not be of unit length.
+    pub fn new(n: &Vec3) -> Self {
+        let w = n.unit();
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0, 1, 0)
+        } else {
+            Vec3::new(1, 0, 0)
+        };
+        let v = w.cross(&a).unit();
+        let u = v.cross(&w);
+        Onb { u, v, w }
+    }
+
+    pub fn u(&self) -> Vec3 {
+        self.u
+    }
+
+    pub fn v(&self) -> Vec3 {
+        self.v
+    }
+
+    pub fn w(&self) -> Vec3 {
+        self.w
+    }
+
+    /// `a`, given in this basis, in world coordinates.
+    pub fn to_world(&self, a: &Vec3) -> Vec3 {
+        self.u * a.x() + self.v * a.y() + self.w * a.z()
+    }
+
+    /// `a`, given in world coordinates, in this basis.
+    pub fn to_local(&self, a: &Vec3) -> Vec3 {
+        Vec3::new(a.dot(&self.u), a.dot(&self.v), a.dot(&self.w))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Onb;
+    use crate::vec3::Vec3;
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1e-12
+    }
+
+    #[test]
+    fn orthonormal_and_right_handed() {
+        for n in [
+            Vec3::new(0, 0, 1),
+            Vec3::new(1, 0, 0),
+            Vec3::new(0, -3, 0),
+            Vec3::new(1, 2, -2),
+        ] {
+            let onb = Onb::new(&n);
+            assert!(close(onb.w(), n.unit()));
+            for axis in [onb.u(), onb.v(), onb.w()] {
+                assert!((axis.length() - 1.0).abs() < 1e-12);
+            }
+            assert!(onb.u().dot(&onb.v()).abs() < 1e-12);
+            assert!(onb.u().dot(&onb.w()).abs() < 1e-12);
+            assert!(close(onb.u().cross(&onb.v()), onb.w()));
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let onb = Onb::new(&Vec3::new(1, 2, -2));
+        assert!(close(onb.to_world(&Vec3::new(0, 0, 1)), onb.w()));
+        let a = Vec3::new(0.3, -1.2, 2.5);
+        assert!(close(onb.to_world(&onb.to_local(&a)), a));
+        assert!(close(onb.to_local(&onb.to_world(&a)), a));
+        assert!((onb.to_local(&a).length() - a.length()).abs() < 1e-12);
+    }
+}
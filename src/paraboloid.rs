@@ -0,0 +1,196 @@
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    cylinder::{axial_u, hit_cap},
+    hittable::{crossing_intervals, HitRecord, Hittable, Interval},
+    material::Material,
+    poly::solve_quadratic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Paraboloid of revolution around the Y axis with its vertex at the origin,
+/// opening up to a rim of `radius` at `height`, closed by a disk there if
+/// `capped`. Place it with a `Transform`. On the side `u` goes around the
+/// axis and `v` up to the rim; the cap uses the polar coordinates of a
+/// `Disk`.
+pub struct Paraboloid {
+    radius: f64,
+    height: f64,
+    capped: bool,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Paraboloid {
+    pub fn new<M: Material + 'static>(radius: f64, height: f64, capped: bool, material: M) -> Self {
+        Paraboloid {
+            radius,
+            height,
+            capped,
+            material: Box::new(material),
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn capped(&self) -> bool {
+        self.capped
+    }
+}
+
+impl Hittable for Paraboloid {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let (o, d) = (r.origin(), r.direction());
+        let mut closest = t_max;
+        // Outward normal and `v` of the closest hit so far.
+        let mut found: Option<(Vec3, f64)> = None;
+
+        // h (x² + z²) = r² y
+        let (h, r2) = (self.height, self.radius * self.radius);
+        let a = h * (d.x() * d.x() + d.z() * d.z());
+        let b = 2.0 * h * (o.x() * d.x() + o.z() * d.z()) - r2 * d.y();
+        let c = h * (o.x() * o.x() + o.z() * o.z()) - r2 * o.y();
+        for t in solve_quadratic(a, b, c) {
+            if t <= t_min || t >= closest {
+                continue;
+            }
+            let p = r.at(t);
+            if p.y() <= h {
+                closest = t;
+                let normal = Vec3::new(2.0 * h * p.x(), -r2, 2.0 * h * p.z()).unit();
+                found = Some((normal, p.y() / h));
+                break;
+            }
+        }
+        if self.capped {
+            if let Some((t, v)) = hit_cap(r, h, self.radius, t_min, closest) {
+                closest = t;
+                found = Some((Vec3::new(0, 1, 0), v));
+            }
+        }
+
+        let (normal, v) = match found {
+            Some(found) => found,
+            None => return false,
+        };
+        rec.t = closest;
+        rec.p = r.at(closest);
+        rec.set_face_normal(r, &normal);
+        rec.u = axial_u(&rec.p);
+        rec.v = v;
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Point3::new(-self.radius, 0, -self.radius),
+            Point3::new(self.radius, self.height, self.radius),
+        ))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.capped
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Paraboloid;
+    use crate::{
+        hittable::hit,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn bowl_and_cap() {
+        // y = x² + z² up to y = 1.
+        let bowl = Paraboloid::new(1.0, 1.0, false, Lambertian::default());
+        let rec = hit(
+            &bowl,
+            &Ray::new(Point3::new(0.5, -5, 0), Vec3::new(0, 1, 0)),
+        )
+        .unwrap();
+        assert!((rec.t - 5.25).abs() < 1e-12);
+        assert_close(rec.normal, Vec3::new(1, -1, 0).unit());
+        assert!(rec.front_face);
+        assert_eq!(rec.v, 0.25);
+
+        // Looking into the open bowl from above hits its inside.
+        let rec = hit(
+            &bowl,
+            &Ray::new(Point3::new(0.5, 5, 0), Vec3::new(0, -1, 0)),
+        )
+        .unwrap();
+        assert!((rec.t - 4.75).abs() < 1e-12);
+        assert!(!rec.front_face);
+        assert_close(rec.normal, Vec3::new(-1, 1, 0).unit());
+
+        let closed = Paraboloid::new(1.0, 1.0, true, Lambertian::default());
+        let rec = hit(
+            &closed,
+            &Ray::new(Point3::new(0.5, 5, 0), Vec3::new(0, -1, 0)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_close(rec.normal, Vec3::new(0, 1, 0));
+        assert!(rec.front_face);
+
+        // Beyond the rim the surface does not continue.
+        assert!(hit(&bowl, &Ray::new(Point3::new(-5, 2, 0), Vec3::new(1, 0, 0))).is_none());
+    }
+
+    #[test]
+    fn tangent_and_grazing_rays() {
+        let bowl = Paraboloid::new(2.0, 4.0, false, Lambertian::default());
+        // At the vertex the horizontal ray touches the bowl.
+        let rec = hit(&bowl, &Ray::new(Point3::new(-5, 0, 0), Vec3::new(1, 0, 0))).unwrap();
+        assert_eq!(rec.t, 5.0);
+        // Perpendicular to the ray, so its side is arbitrary.
+        assert!((rec.normal.dot(&Vec3::new(0, -1, 0)).abs() - 1.0).abs() < 1e-12);
+        assert!(hit(
+            &bowl,
+            &Ray::new(Point3::new(-5, -1e-9, 0), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+        let rec = hit(
+            &bowl,
+            &Ray::new(Point3::new(-5, 1e-9, 0), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert!((rec.p.y() - rec.p.x() * rec.p.x()).abs() < 1e-9);
+
+        // Tangent to the side: the line y = 2x - 1 touches y = x² at x = 1.
+        let rec = hit(&bowl, &Ray::new(Point3::new(-1, -3, 0), Vec3::new(1, 2, 0))).unwrap();
+        assert!((rec.t - 2.0).abs() < 1e-9);
+        assert!((rec.normal.dot(&Vec3::new(2, -1, 0).unit()).abs() - 1.0).abs() < 1e-9);
+
+        // Parallel to the axis the ray meets the surface once.
+        let rec = hit(&bowl, &Ray::new(Point3::new(1, -1, 1), Vec3::new(0, 1, 0))).unwrap();
+        assert_eq!(rec.t, 3.0);
+    }
+}
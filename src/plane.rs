@@ -0,0 +1,139 @@
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    onb::Onb,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Ray parameter where `r` meets the plane through `point` with normal
+/// `normal`, if it is within (`t_min`, `t_max`).
+pub(crate) fn intersect_plane(
+    r: &Ray,
+    point: &Point3,
+    normal: &Vec3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<f64> {
+    let denom = normal.dot(r.direction());
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let t = (point - r.origin()).dot(normal) / denom;
+    if t < t_max && t > t_min {
+        return Some(t);
+    }
+    return None;
+}
+
+/// Infinite plane through `point`. Its texture coordinates are distances
+/// in the plane from `point`, so textures repeat every unit.
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Plane {
+    /// `normal` sets the front side of the plane and need not be unit.
+    pub fn new<M: Material + 'static>(point: Point3, normal: Vec3, material: M) -> Self {
+        let normal = normal.unit();
+        let frame = Onb::new(&normal);
+        Plane {
+            point,
+            normal,
+            tangent: frame.u(),
+            bitangent: frame.v(),
+            material: Box::new(material),
+        }
+    }
+
+    pub fn point(&self) -> &Point3 {
+        &self.point
+    }
+
+    pub fn normal(&self) -> &Vec3 {
+        &self.normal
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let t = match intersect_plane(r, &self.point, &self.normal, t_min, t_max) {
+            Some(t) => t,
+            None => return false,
+        };
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.set_face_normal(r, &self.normal);
+        let d = rec.p - self.point;
+        rec.u = d.dot(&self.tangent);
+        rec.v = d.dot(&self.bitangent);
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Plane;
+    use crate::{
+        hittable::{test_rng, HitRecord, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    fn ground() -> Plane {
+        Plane::new(
+            Point3::new(0, -0.5, 0),
+            Vec3::new(0, 2, 0),
+            Lambertian::default(),
+        )
+    }
+
+    #[test]
+    fn hit_from_both_sides() {
+        let plane = ground();
+        let mut rec = HitRecord::default();
+        let down = Ray::new(Point3::new(3, 1.5, -7), Vec3::new(0, -1, 0));
+        assert!(plane.hit(&down, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.p, Point3::new(3, -0.5, -7));
+        assert_eq!(rec.normal, Vec3::new(0, 1, 0));
+        assert!(rec.front_face);
+        assert!((rec.u * rec.u + rec.v * rec.v - 58.0).abs() < 1e-9);
+
+        let up = Ray::new(Point3::new(0, -2, 0), Vec3::new(0, 1, 0));
+        assert!(plane.hit(&up, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.normal, Vec3::new(0, -1, 0));
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn misses() {
+        let plane = ground();
+        let mut rec = HitRecord::default();
+        let parallel = Ray::new(Point3::new(0, 0, 0), Vec3::new(1, 0, 0));
+        let away = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 1, 0));
+        assert!(!plane.hit(&parallel, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!(!plane.hit(&away, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!(plane.bounding_box().is_none());
+    }
+}
@@ -0,0 +1,245 @@
+//! Real roots of low-degree polynomials, for ray/surface intersections.
+//! Every solver returns its roots in increasing order, repeated roots once.
+
+use std::f64::consts::PI;
+
+/// Relative size under which a discriminant is taken to be zero, so that
+/// tangent rays find their double root.
+const EPSILON: f64 = 1e-12;
+
+/// Roots of `a x² + b x + c`, falling back to the linear equation when `a`
+/// is zero.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 {
+        if b == 0.0 {
+            return vec![];
+        }
+        return vec![-c / b];
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    let scale = (b * b).max((4.0 * a * c).abs());
+    if discriminant < -EPSILON * scale {
+        return vec![];
+    }
+    if discriminant <= EPSILON * scale {
+        return vec![-b / (2.0 * a)];
+    }
+    // Avoids the cancellation of -b + sqrt(disc) when b is large.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (x0, x1) = if q == 0.0 {
+        let x = (-c / a).sqrt();
+        (-x, x)
+    } else {
+        (q / a, c / q)
+    };
+    return if x0 < x1 { vec![x0, x1] } else { vec![x1, x0] };
+}
+
+/// Roots of `a x³ + b x² + c x + d`.
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return solve_quadratic(b, c, d);
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+    // Depressed cubic t³ + p t + q with x = t - b/3.
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let half_q = q / 2.0;
+    let third_p = p / 3.0;
+    let discriminant = half_q * half_q + third_p * third_p * third_p;
+
+    let mut roots = if p.abs() <= EPSILON && q.abs() <= EPSILON {
+        vec![0.0]
+    } else if discriminant > EPSILON * (half_q * half_q).max(EPSILON) {
+        let s = discriminant.sqrt();
+        vec![(-half_q + s).cbrt() + (-half_q - s).cbrt()]
+    } else if discriminant < -EPSILON * (half_q * half_q).max(EPSILON) {
+        // Three distinct real roots.
+        let r = (-third_p).sqrt();
+        let phi = (-half_q / (r * r * r)).clamp(-1.0, 1.0).acos();
+        (0..3)
+            .map(|k| 2.0 * r * ((phi + 2.0 * PI * k as f64) / 3.0).cos())
+            .collect()
+    } else {
+        // A double root and a simple one.
+        let u = (-half_q).cbrt();
+        vec![2.0 * u, -u]
+    };
+    for t in roots.iter_mut() {
+        *t -= shift;
+        *t = polish(&[1.0, b, c, d], *t);
+    }
+    return sorted(roots);
+}
+
+/// Roots of `a x⁴ + b x³ + c x² + d x + e`, by Ferrari's method followed
+/// by Newton polishing against the original polynomial.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    // Depressed quartic y⁴ + p y² + q y + r with x = y - b/4.
+    let shift = b / 4.0;
+    let b2 = b * b;
+    let p = c - 3.0 * b2 / 8.0;
+    let q = d - b * c / 2.0 + b2 * b / 8.0;
+    let r = e - b * d / 4.0 + b2 * c / 16.0 - 3.0 * b2 * b2 / 256.0;
+
+    let mut roots = vec![];
+    if q.abs() <= EPSILON * (1.0 + p.abs() + r.abs()) {
+        // Biquadratic: solve for y².
+        for z in solve_quadratic(1.0, p, r) {
+            if z > 0.0 {
+                let y = z.sqrt();
+                roots.push(-y);
+                roots.push(y);
+            } else if z > -EPSILON {
+                roots.push(0.0);
+            }
+        }
+    } else {
+        // Largest root of the resolvent cubic, positive since q != 0.
+        let m = solve_cubic(1.0, p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m > 0.0 {
+            let s = (2.0 * m).sqrt();
+            let t = q / (2.0 * s);
+            roots.extend(solve_quadratic(1.0, s, p / 2.0 + m - t));
+            roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + t));
+        }
+    }
+    let coefficients = [1.0, b, c, d, e];
+    for y in roots.iter_mut() {
+        *y = polish(&coefficients, *y - shift);
+    }
+    return sorted(roots);
+}
+
+/// Value of the polynomial with `coefficients`, highest degree first.
+pub fn eval(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().fold(0.0, |acc, c| acc * x + c)
+}
+
+/// A few Newton steps, kept only while they reduce the residual.
+fn polish(coefficients: &[f64], x: f64) -> f64 {
+    let n = coefficients.len() - 1;
+    let mut x = x;
+    let mut residual = eval(coefficients, x).abs();
+    for _ in 0..4 {
+        let derivative = coefficients[..n]
+            .iter()
+            .enumerate()
+            .fold(0.0, |acc, (i, c)| acc * x + c * (n - i) as f64);
+        if derivative == 0.0 {
+            break;
+        }
+        let next = x - eval(coefficients, x) / derivative;
+        let next_residual = eval(coefficients, next).abs();
+        if next_residual.is_nan() || next_residual >= residual {
+            break;
+        }
+        x = next;
+        residual = next_residual;
+    }
+    return x;
+}
+
+/// Sorts `roots` and merges the ones that are equal up to rounding.
+fn sorted(mut roots: Vec<f64>) -> Vec<f64> {
+    roots.retain(|x| x.is_finite());
+    roots.sort_by(f64::total_cmp);
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-9 * (1.0 + b.abs()));
+    return roots;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eval, solve_cubic, solve_quadratic, solve_quartic};
+
+    fn assert_roots(found: Vec<f64>, expected: &[f64]) {
+        assert_eq!(found.len(), expected.len(), "{:?} != {:?}", found, expected);
+        for (f, e) in found.iter().zip(expected) {
+            assert!(
+                (f - e).abs() < 1e-9 * (1.0 + e.abs()),
+                "{:?} != {:?}",
+                found,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn quadratic() {
+        assert_roots(solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(solve_quadratic(1.0, -2.0, 1.0), &[1.0]);
+        assert_roots(solve_quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(solve_quadratic(0.0, 2.0, -1.0), &[0.5]);
+        assert_roots(solve_quadratic(1.0, 0.0, -4.0), &[-2.0, 2.0]);
+        // No cancellation for a tiny root next to a huge one.
+        assert_roots(solve_quadratic(1.0, -1e8, 1.0), &[1e-8, 1e8]);
+    }
+
+    #[test]
+    fn cubic() {
+        assert_roots(solve_cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        assert_roots(solve_cubic(2.0, 0.0, 0.0, -16.0), &[2.0]);
+        assert_roots(solve_cubic(1.0, -5.0, 8.0, -4.0), &[1.0, 2.0]);
+        assert_roots(solve_cubic(1.0, -3.0, 3.0, -1.0), &[1.0]);
+        assert_roots(solve_cubic(0.0, 1.0, -3.0, 2.0), &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(
+            solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+        );
+        // (x² + 1)(x - 2)(x + 5)
+        assert_roots(solve_quartic(1.0, 3.0, -9.0, 3.0, -10.0), &[-5.0, 2.0]);
+        // Biquadratic (x² - 1)(x² - 4)
+        assert_roots(
+            solve_quartic(2.0, 0.0, -10.0, 0.0, 8.0),
+            &[-2.0, -1.0, 1.0, 2.0],
+        );
+        // Double roots (x - 1)²(x + 3)²
+        assert_roots(solve_quartic(1.0, 4.0, -2.0, -12.0, 9.0), &[-3.0, 1.0]);
+        assert_roots(solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+    }
+
+    #[test]
+    fn quartic_roots_are_accurate() {
+        // Widely spread roots, as met by rays starting far from a torus.
+        let roots = [-0.001, 0.37, 25.0, 1000.0];
+        let coefficients = [
+            1.0,
+            -(roots[0] + roots[1] + roots[2] + roots[3]),
+            roots[0] * roots[1]
+                + roots[0] * roots[2]
+                + roots[0] * roots[3]
+                + roots[1] * roots[2]
+                + roots[1] * roots[3]
+                + roots[2] * roots[3],
+            -(roots[0] * roots[1] * roots[2]
+                + roots[0] * roots[1] * roots[3]
+                + roots[0] * roots[2] * roots[3]
+                + roots[1] * roots[2] * roots[3]),
+            roots[0] * roots[1] * roots[2] * roots[3],
+        ];
+        let found = solve_quartic(
+            coefficients[0],
+            coefficients[1],
+            coefficients[2],
+            coefficients[3],
+            coefficients[4],
+        );
+        assert_eq!(found.len(), 4, "{:?}", found);
+        for (f, e) in found.iter().zip(roots) {
+            assert!((f - e).abs() < 1e-6 * (1.0 + e.abs()), "{:?}", found);
+        }
+        assert!(eval(&coefficients, found[1]).abs() < 1e-3);
+    }
+}
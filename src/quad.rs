@@ -0,0 +1,260 @@
+use rand::{Rng, RngCore};
+
+use crate::{
+    aabb::{Aabb, BBOX_PADDING},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    plane::intersect_plane,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Parallelogram with a corner at `q` and edges `u` and `v`. Its front side
+/// is the one `u × v` points to, and `(u, v)` texture coordinates run from 0
+/// to 1 along the edges.
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    /// `n / (n · n)` for the unnormalized normal `n`, used to find the
+    /// planar coordinates of a hit.
+    w: Vec3,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Quad {
+    pub fn new<M: Material + 'static>(q: Point3, u: Vec3, v: Vec3, material: M) -> Self {
+        let n = u.cross(&v);
+        Quad {
+            q,
+            u,
+            v,
+            normal: n.unit(),
+            w: n / n.length_squared(),
+            material: Box::new(material),
+        }
+    }
+
+    /// Rectangle `[x0, x1] × [y0, y1]` in the plane `z = k`, facing +Z.
+    pub fn xy<M: Material + 'static>(
+        x0: f64,
+        x1: f64,
+        y0: f64,
+        y1: f64,
+        k: f64,
+        material: M,
+    ) -> Self {
+        Self::new(
+            Point3::new(x0, y0, k),
+            Vec3::new(x1 - x0, 0, 0),
+            Vec3::new(0, y1 - y0, 0),
+            material,
+        )
+    }
+
+    /// Rectangle `[x0, x1] × [z0, z1]` in the plane `y = k`, facing +Y.
+    pub fn xz<M: Material + 'static>(
+        x0: f64,
+        x1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: M,
+    ) -> Self {
+        Self::new(
+            Point3::new(x0, k, z0),
+            Vec3::new(0, 0, z1 - z0),
+            Vec3::new(x1 - x0, 0, 0),
+            material,
+        )
+    }
+
+    /// Rectangle `[y0, y1] × [z0, z1]` in the plane `x = k`, facing +X.
+    pub fn yz<M: Material + 'static>(
+        y0: f64,
+        y1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: M,
+    ) -> Self {
+        Self::new(
+            Point3::new(k, y0, z0),
+            Vec3::new(0, y1 - y0, 0),
+            Vec3::new(0, 0, z1 - z0),
+            material,
+        )
+    }
+
+    pub fn corner(&self) -> &Point3 {
+        &self.q
+    }
+
+    pub fn edges(&self) -> (&Vec3, &Vec3) {
+        (&self.u, &self.v)
+    }
+
+    pub fn normal(&self) -> &Vec3 {
+        &self.normal
+    }
+
+    pub fn area(&self) -> f64 {
+        self.u.cross(&self.v).length()
+    }
+
+    /// Parameter of the point where `r` crosses the quad within
+    /// `[t_min, t_max]`, with its coordinates along the two edges.
+    fn crossing(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+        let t = intersect_plane(r, &self.q, &self.normal, t_min, t_max)?;
+        let planar = r.at(t) - self.q;
+        let alpha = self.w.dot(&planar.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+        return Some((t, alpha, beta));
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let (t, alpha, beta) = match self.crossing(r, t_min, t_max) {
+            Some(crossing) => crossing,
+            None => return false,
+        };
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.u = alpha;
+        rec.v = beta;
+        rec.set_face_normal(r, &self.normal);
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let far = self.q + self.u + self.v;
+        let diagonal = Aabb::new(self.q, far);
+        let other = Aabb::new(self.q + self.u, self.q + self.v);
+        Some(diagonal.surrounding(&other).padded(BBOX_PADDING))
+    }
+
+    /// Uniform over the area of the quad, converted to solid angle.
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let t = match self.crossing(&Ray::new(*origin, *direction), 0.001, f64::INFINITY) {
+            Some((t, _, _)) => t,
+            None => return 0.0,
+        };
+        let distance_squared = t * t * direction.length_squared();
+        let cosine = (direction.dot(&self.normal) / direction.length()).abs();
+        return distance_squared / (cosine * self.area());
+    }
+
+    fn random(&self, origin: &Point3, rng: &mut Box<dyn RngCore>) -> Vec3 {
+        let p = self.q + self.u * rng.gen::<f64>() + self.v * rng.gen::<f64>();
+        return p - *origin;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::Quad;
+    use crate::{
+        hittable::{hit, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn axis_aligned() {
+        let quad = Quad::xy(-1.0, 3.0, 0.0, 2.0, -5.0, Lambertian::default());
+        assert_eq!(*quad.normal(), Vec3::new(0, 0, 1));
+        assert_eq!(quad.area(), 8.0);
+        let rec = hit(
+            &quad,
+            &Ray::new(Point3::new(0, 0.5, 0), Vec3::new(0, 0, -1)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 5.0);
+        assert!(rec.front_face);
+        assert_eq!((rec.u, rec.v), (0.25, 0.25));
+        assert!(hit(
+            &quad,
+            &Ray::new(Point3::new(3.5, 0.5, 0), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+
+        let floor = Quad::xz(0.0, 1.0, 0.0, 1.0, 0.0, Lambertian::default());
+        assert_eq!(*floor.normal(), Vec3::new(0, 1, 0));
+        let wall = Quad::yz(0.0, 1.0, 0.0, 1.0, 0.0, Lambertian::default());
+        assert_eq!(*wall.normal(), Vec3::new(1, 0, 0));
+    }
+
+    #[test]
+    fn slanted_parallelogram() {
+        let quad = Quad::new(
+            Point3::new(0, 0, 0),
+            Vec3::new(2, 0, 0),
+            Vec3::new(1, 1, -1),
+            Lambertian::default(),
+        );
+        let r = Ray::new(Point3::new(2, 0.5, 5), Vec3::new(0, 0, -1));
+        let rec = hit(&quad, &r).unwrap();
+        assert!((rec.p - Point3::new(2, 0.5, -0.5)).length() < 1e-12);
+        assert!((rec.u - 0.75).abs() < 1e-12 && (rec.v - 0.5).abs() < 1e-12);
+        // Back side.
+        let r = Ray::new(Point3::new(2, 0.5, -5), Vec3::new(0, 0, 1));
+        let rec = hit(&quad, &r).unwrap();
+        assert!(!rec.front_face);
+        assert_eq!(rec.normal, -*quad.normal());
+        // Outside of the slanted edge.
+        assert!(hit(
+            &quad,
+            &Ray::new(Point3::new(0.2, 0.8, 5), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn bounding_box_is_padded() {
+        let quad = Quad::xz(0.0, 1.0, 0.0, 2.0, 3.0, Lambertian::default());
+        let bbox = quad.bounding_box().unwrap();
+        assert!(bbox.min().y() < 3.0 && bbox.max().y() > 3.0);
+        assert_eq!((bbox.min().x(), bbox.max().z()), (0.0, 2.0));
+        let r = Ray::new(Point3::new(0.5, 5, 1), Vec3::new(0, -1, 0));
+        assert!(bbox.hit(&r, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn samples_by_area() {
+        let quad = Quad::xz(-1.0, 1.0, -1.0, 1.0, 2.0, Lambertian::default());
+        let origin = Point3::new(0, 0, 0);
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        for _ in 0..1000 {
+            let direction = quad.random(&origin, &mut rng);
+            assert!(hit(&quad, &Ray::new(origin, direction)).is_some());
+            assert!(quad.pdf_value(&origin, &direction) > 0.0);
+        }
+        // Straight up: distance squared over area, the quad facing us.
+        assert!((quad.pdf_value(&origin, &Vec3::new(0, 5, 0)) - 1.0).abs() < 1e-12);
+        assert_eq!(quad.pdf_value(&origin, &Vec3::new(0, -1, 0)), 0.0);
+        let n = 200_000;
+        let total: f64 = (0..n)
+            .map(|_| quad.pdf_value(&origin, &Vec3::random_unit_vector(&mut rng)))
+            .sum();
+        assert!((total / n as f64 * 4.0 * PI - 1.0).abs() < 0.03);
+    }
+}
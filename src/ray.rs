@@ -3,13 +3,21 @@ use crate::vec3::{Point3, Vec3};
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    time: f64,
 }
 
 impl Ray {
     pub fn new(point: Point3, dir: Vec3) -> Ray {
+        Ray::with_time(point, dir, 0.0)
+    }
+
+    /// Ray cast at `time` within the camera shutter interval; moving
+    /// objects are intersected where they are at that time.
+    pub fn with_time(point: Point3, dir: Vec3, time: f64) -> Ray {
         Ray {
             orig: point,
-            dir: dir,
+            dir,
+            time,
         }
     }
 
@@ -21,6 +29,10 @@ impl Ray {
         return &self.dir;
     }
 
+    pub fn time(&self) -> f64 {
+        return self.time;
+    }
+
     pub fn at<T: Into<f64>>(&self, t: T) -> Point3 {
         return &self.orig + &(&self.dir * t.into());
     }
@@ -34,7 +46,7 @@ mod tests {
     #[test]
     fn origin() {
         let point = Point3::new(1, 2, 3);
-        let ray = Ray::new(point.clone(), Vec3::new(3, 1, 2));
+        let ray = Ray::new(point, Vec3::new(3, 1, 2));
 
         assert_eq!(*ray.origin(), point)
     }
@@ -42,8 +54,16 @@ mod tests {
     #[test]
     fn dir() {
         let dir = Vec3::new(1, 2, 3);
-        let ray = Ray::new(Vec3::new(3, 1, 2), dir.clone());
+        let ray = Ray::new(Vec3::new(3, 1, 2), dir);
 
         assert_eq!(*ray.direction(), dir)
     }
+
+    #[test]
+    fn time() {
+        let point = Point3::new(1, 2, 3);
+        let dir = Vec3::new(3, 1, 2);
+        assert_eq!(Ray::new(point, dir).time(), 0.0);
+        assert_eq!(Ray::with_time(point, dir, 0.5).time(), 0.5);
+    }
 }
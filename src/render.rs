@@ -0,0 +1,770 @@
+use indicatif::ProgressBar;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::{
+    camera::Camera,
+    film::Film,
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+    vec3::{Color, Vec3},
+};
+
+/// Width and height in pixels of the square tiles handed to the workers.
+pub const TILE_SIZE: u32 = 16;
+
+/// What a ray sees when it leaves the scene without hitting anything.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    /// Blend from `horizon` to `zenith` with the height of the ray direction.
+    Gradient {
+        horizon: Color,
+        zenith: Color,
+    },
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, r: &Ray) -> Color {
+        match self {
+            Background::Gradient { horizon, zenith } => {
+                let unit_direction = r.direction().unit();
+                let t = 0.5 * (unit_direction.y() + 1.0);
+                *horizon * (1.0 - t) + *zenith * t
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Gradient {
+            horizon: Color::new(1.0, 1.0, 1.0),
+            zenith: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+/// Homogeneous fog filling the whole scene. Per unit of distance, light is
+/// absorbed with rate `absorption` and scattered into every direction with
+/// rate `scattering`. The background stands at the edge of the fog, so
+/// rays leaving the scene see it clearly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    pub absorption: f64,
+    pub scattering: f64,
+}
+
+impl Fog {
+    /// Rate at which light is lost from a ray, by either process.
+    pub fn extinction(&self) -> f64 {
+        self.absorption + self.scattering
+    }
+
+    /// Share of the lost light that is scattered rather than absorbed.
+    pub fn albedo(&self) -> f64 {
+        if self.extinction() > 0.0 {
+            self.scattering / self.extinction()
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderSettings {
+    pub image_width: u32,
+    pub image_height: u32,
+    pub samples_per_pixel: usize,
+    pub max_depth: usize,
+    /// Bounces after which paths may be ended by Russian roulette.
+    pub roulette_depth: usize,
+    /// Drives every random decision of the render; equal seeds give equal
+    /// images.
+    pub seed: u64,
+    pub background: Background,
+    pub fog: Option<Fog>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tile {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+fn tiles(settings: &RenderSettings) -> Vec<Tile> {
+    let mut tiles = vec![];
+    for y in (0..settings.image_height).step_by(TILE_SIZE as usize) {
+        for x in (0..settings.image_width).step_by(TILE_SIZE as usize) {
+            tiles.push(Tile {
+                x,
+                y,
+                width: TILE_SIZE.min(settings.image_width - x),
+                height: TILE_SIZE.min(settings.image_height - y),
+            });
+        }
+    }
+    tiles
+}
+
+/// Whether `rec`, the first hit of `r` in the world, lies on one of the
+/// lights. The lights share their objects with the world, so they report
+/// the very same `t`.
+fn on_light<L: Hittable + ?Sized>(
+    lights: &L,
+    r: &Ray,
+    rec: &HitRecord,
+    rng: &mut Box<dyn RngCore>,
+) -> bool {
+    let mut light = HitRecord::default();
+    lights.hit(r, 0.001, f64::INFINITY, &mut light, rng) && light.t == rec.t
+}
+
+/// Weight of a sample drawn with density `pdf` among samples also drawn
+/// with density `other`, by the power heuristic.
+fn power_heuristic(pdf: f64, other: f64) -> f64 {
+    pdf * pdf / (pdf * pdf + other * other)
+}
+
+/// Light reaching the hit `rec` straight from the lights and leaving along
+/// `wo`, estimated with one shadow ray towards a random point on them and
+/// weighted against sampling the material.
+fn direct_light<T: Hittable + ?Sized, L: Hittable + ?Sized>(
+    rec: &HitRecord,
+    wo: &Vec3,
+    time: f64,
+    settings: &RenderSettings,
+    world: &T,
+    lights: &L,
+    rng: &mut Box<dyn RngCore>,
+) -> Color {
+    let wi = lights.random(&rec.p, rng);
+    let light_pdf = lights.pdf_value(&rec.p, &wi);
+    if light_pdf <= 0.0 {
+        return Color::default();
+    }
+    let bsdf = rec.material.eval(rec, &wi, wo);
+    if bsdf == Color::default() {
+        return Color::default();
+    }
+    let shadow = Ray::with_time(rec.p, wi, time);
+    let mut hit = HitRecord::default();
+    if !world.hit(&shadow, 0.001, f64::INFINITY, &mut hit, rng)
+        || !on_light(lights, &shadow, &hit, rng)
+    {
+        return Color::default();
+    }
+    let transmittance = match &settings.fog {
+        Some(fog) => (-fog.extinction() * hit.t * wi.length()).exp(),
+        None => 1.0,
+    };
+    let weight = power_heuristic(light_pdf, rec.material.pdf(rec, &wi, wo));
+    return bsdf * hit.material.emitted(&hit) * (transmittance * weight / light_pdf);
+}
+
+/// Radiance arriving along `r` from the `world`, following at most `depth`
+/// bounces. Hits that are not specular are also lit straight from `lights`,
+/// which must be part of the world too, combining both kinds of sample by
+/// multiple importance sampling; pass an empty list to rely on bounces
+/// alone. After `settings.roulette_depth` bounces, paths that carry little
+/// light are ended at random by Russian roulette, and the survivors carry
+/// more to make up for them.
+pub fn ray_color<T: Hittable + ?Sized, L: Hittable + ?Sized>(
+    r: Ray,
+    settings: &RenderSettings,
+    world: &T,
+    lights: &L,
+    depth: usize,
+    rng: &mut Box<dyn RngCore>,
+) -> Color {
+    let mut color = Color::default();
+    // Fraction of the light found from here on that reaches the camera.
+    let mut throughput = Color::new(1, 1, 1);
+    let mut next = r;
+    // Density with which a material picked the direction of `next`, if the
+    // lights were sampled from there too.
+    let mut bsdf_pdf: Option<f64> = None;
+    for bounce in 0..depth {
+        let r = next;
+        let mut rec: HitRecord = HitRecord::default();
+        if !world.hit(&r, 0.001, f64::INFINITY, &mut rec, rng) {
+            return color + throughput * settings.background.color(&r);
+        }
+        let mut fogged = None;
+        if let Some(fog) = &settings.fog {
+            let length = r.direction().length();
+            let distance = -(1.0 - rng.gen::<f64>()).ln() / fog.extinction();
+            if distance < rec.t * length {
+                fogged = Some((r.at(distance / length), fog.albedo()));
+            }
+        }
+        if let Some((p, albedo)) = fogged {
+            throughput *= albedo;
+            next = Ray::with_time(p, Vec3::random_unit_vector(rng), r.time());
+            bsdf_pdf = None;
+        } else {
+            let mut emitted = rec.material.emitted(&rec);
+            if let Some(pdf) = bsdf_pdf {
+                if on_light(lights, &r, &rec, rng) {
+                    emitted *= power_heuristic(pdf, lights.pdf_value(r.origin(), r.direction()));
+                }
+            }
+            color += throughput * emitted;
+            let wo = -*r.direction();
+            let scattered = rec.material.scatter(&r, &rec, rng);
+            bsdf_pdf = match &scattered {
+                Some(s) if s.specular => None,
+                _ => {
+                    let direct = direct_light(&rec, &wo, r.time(), settings, world, lights, rng);
+                    color += throughput * direct;
+                    scattered.as_ref().map(|s| s.pdf)
+                }
+            };
+            let scattered = match scattered {
+                Some(scattered) => scattered,
+                None => return color,
+            };
+            throughput = throughput * scattered.attenuation();
+            next = scattered.ray;
+        }
+        if bounce + 1 >= settings.roulette_depth {
+            let survival = throughput.x().max(throughput.y()).max(throughput.z());
+            if survival < 1.0 {
+                if rng.gen::<f64>() >= survival {
+                    return color;
+                }
+                throughput /= survival;
+            }
+        }
+    }
+    return color;
+}
+
+/// RNG for the pixel at (`x`, `y`): every pixel reads its own ChaCha stream
+/// of the render seed, so the image does not depend on how the work is split.
+pub fn pixel_rng(settings: &RenderSettings, x: u32, y: u32) -> Box<dyn RngCore> {
+    let mut rng = ChaCha8Rng::seed_from_u64(settings.seed);
+    rng.set_stream(y as u64 * settings.image_width as u64 + x as u64);
+    Box::new(rng)
+}
+
+/// Average radiance over the samples of the pixel at (`x`, `y`).
+pub fn render_pixel<T: Hittable + ?Sized, L: Hittable + ?Sized>(
+    x: u32,
+    y: u32,
+    world: &T,
+    lights: &L,
+    camera: &Camera,
+    settings: &RenderSettings,
+) -> Color {
+    let mut rng = pixel_rng(settings, x, y);
+    let image_width = settings.image_width;
+    let image_height = settings.image_height;
+    let y = image_height - y;
+    let mut color = Color::new(0, 0, 0);
+    for _ in 0..settings.samples_per_pixel {
+        let u = (x as f64 + rng.gen::<f64>()) / (image_width - 1) as f64;
+        let v = (y as f64 + rng.gen::<f64>()) / (image_height - 1) as f64;
+        let r = camera.get_ray(u, v, &mut rng);
+        color += ray_color(r, settings, world, lights, settings.max_depth, &mut rng);
+    }
+    color / settings.samples_per_pixel as f64
+}
+
+/// Renders one tile, returning the pixels row by row.
+fn render_tile<T: Hittable + ?Sized, L: Hittable + ?Sized>(
+    tile: &Tile,
+    world: &T,
+    lights: &L,
+    camera: &Camera,
+    settings: &RenderSettings,
+    bar: &ProgressBar,
+) -> Vec<Color> {
+    let mut pixels = Vec::with_capacity((tile.width * tile.height) as usize);
+    for y in tile.y..(tile.y + tile.height) {
+        for x in tile.x..(tile.x + tile.width) {
+            pixels.push(render_pixel(x, y, world, lights, camera, settings));
+        }
+        bar.inc(tile.width.into());
+    }
+    pixels
+}
+
+fn assemble(settings: &RenderSettings, rendered: Vec<(Tile, Vec<Color>)>) -> Film {
+    let mut film = Film::new(settings.image_width, settings.image_height);
+    for (tile, pixels) in rendered {
+        for (i, pixel) in pixels.into_iter().enumerate() {
+            let i = i as u32;
+            film.set(tile.x + i % tile.width, tile.y + i / tile.width, pixel);
+        }
+    }
+    film
+}
+
+/// Renders the image on the current thread, tile by tile.
+pub fn render_serial<T: Hittable + ?Sized, L: Hittable + ?Sized>(
+    world: &T,
+    lights: &L,
+    camera: &Camera,
+    settings: &RenderSettings,
+    bar: &ProgressBar,
+) -> Film {
+    let rendered = tiles(settings)
+        .into_iter()
+        .map(|tile| {
+            (
+                tile,
+                render_tile(&tile, world, lights, camera, settings, bar),
+            )
+        })
+        .collect();
+    assemble(settings, rendered)
+}
+
+/// Renders the tiles on the rayon thread pool. Pixels draw from their own RNG
+/// streams, so the result matches [`render_serial`] for the same seed.
+pub fn render<T: Hittable + ?Sized, L: Hittable + ?Sized>(
+    world: &T,
+    lights: &L,
+    camera: &Camera,
+    settings: &RenderSettings,
+    bar: &ProgressBar,
+) -> Film {
+    let rendered = tiles(settings)
+        .into_par_iter()
+        .map(|tile| {
+            (
+                tile,
+                render_tile(&tile, world, lights, camera, settings, bar),
+            )
+        })
+        .collect();
+    assemble(settings, rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use indicatif::ProgressBar;
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::{
+        ray_color, render, render_pixel, render_serial, tiles, Background, Fog, RenderSettings,
+    };
+    use crate::{
+        aabb::Aabb,
+        camera::Camera,
+        hittable::{HitRecord, Hittable, HittableList},
+        material::{
+            dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian,
+            metal::Metal,
+        },
+        quad::Quad,
+        ray::Ray,
+        sphere::Sphere,
+        vec3::{Color, Point3, Vec3},
+    };
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            image_width: 40,
+            image_height: 22,
+            samples_per_pixel: 4,
+            max_depth: 8,
+            roulette_depth: 5,
+            seed: 42,
+            background: Background::default(),
+            fog: None,
+        }
+    }
+
+    fn no_lights() -> HittableList<'static> {
+        HittableList::new()
+    }
+
+    fn world() -> HittableList<'static> {
+        let mut world = HittableList::new();
+        world.add(Sphere::new(
+            Point3::new(0, 0, -1),
+            0.5,
+            Dielectric::new(1.5),
+        ));
+        world.add(Sphere::new(
+            Point3::new(0, -100.5, -1),
+            100.0,
+            Lambertian::new(Color::new(0.8, 0.8, 0.0)),
+        ));
+        world.add(Sphere::new(
+            Point3::new(-1, 0, -1),
+            0.5,
+            Metal::new(Color::new(0.8, 0.6, 0.2), 0.3),
+        ));
+        world
+    }
+
+    #[test]
+    fn tiles_cover_image() {
+        let settings = settings();
+        let covered: u32 = tiles(&settings).iter().map(|t| t.width * t.height).sum();
+        assert_eq!(covered, settings.image_width * settings.image_height);
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let settings = settings();
+        let world = world();
+        let camera = Camera::default();
+        let bar = ProgressBar::hidden();
+        let serial = render_serial(&world, &no_lights(), &camera, &settings, &bar);
+        let parallel = render(&world, &no_lights(), &camera, &settings, &bar);
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let world = world();
+        let camera = Camera::default();
+        let bar = ProgressBar::hidden();
+        let a = render(&world, &no_lights(), &camera, &settings(), &bar);
+        let b = render(
+            &world,
+            &no_lights(),
+            &camera,
+            &RenderSettings {
+                seed: 7,
+                ..settings()
+            },
+            &bar,
+        );
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn same_seed_is_reproducible() {
+        let world = world();
+        let camera = Camera::default();
+        let bar = ProgressBar::hidden();
+        let a = render(&world, &no_lights(), &camera, &settings(), &bar);
+        let b = render(&world, &no_lights(), &camera, &settings(), &bar);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn pixel_does_not_depend_on_tiling() {
+        let settings = settings();
+        let world = world();
+        let camera = Camera::default();
+        let img = render(
+            &world,
+            &no_lights(),
+            &camera,
+            &settings,
+            &ProgressBar::hidden(),
+        );
+        for (x, y) in [(0, 0), (17, 3), (39, 21), (20, 11)] {
+            assert_eq!(
+                img.get(x, y),
+                render_pixel(x, y, &world, &no_lights(), &camera, &settings)
+            );
+        }
+    }
+
+    #[test]
+    fn background_gradient() {
+        let background = Background::default();
+        let up = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 1, 0));
+        let down = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, -1, 0));
+        assert_eq!(background.color(&up), Color::new(0.5, 0.7, 1.0));
+        assert_eq!(background.color(&down), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn emitters_light_black_background() {
+        let mut world = HittableList::new();
+        world.add(Sphere::new(
+            Point3::new(0, 0, -2),
+            0.5,
+            DiffuseLight::new(Color::new(3, 2, 1)),
+        ));
+        let settings = RenderSettings {
+            background: Background::Solid(Color::default()),
+            ..settings()
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let at_light = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1));
+        let away = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, 1));
+        assert_eq!(
+            ray_color(at_light, &settings, &world, &no_lights(), 10, &mut rng),
+            Color::new(3, 2, 1)
+        );
+        assert_eq!(
+            ray_color(away, &settings, &world, &no_lights(), 10, &mut rng),
+            Color::default()
+        );
+    }
+
+    #[test]
+    fn fog_dims_distant_objects() {
+        let mut world = HittableList::new();
+        world.add(Sphere::new(
+            Point3::new(0, 0, -11),
+            1.0,
+            DiffuseLight::new(Color::new(1, 1, 1)),
+        ));
+        let fog = Fog {
+            absorption: 0.1,
+            scattering: 0.0,
+        };
+        let settings = RenderSettings {
+            background: Background::Solid(Color::new(0.5, 0.5, 0.5)),
+            fog: Some(fog),
+            ..settings()
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let n = 20_000;
+        let mut mean = Color::default();
+        for _ in 0..n {
+            let at_light = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -2));
+            mean += ray_color(at_light, &settings, &world, &no_lights(), 10, &mut rng) / n as f64;
+        }
+        // Beer-Lambert over the ten units up to the light.
+        assert!((mean.x() - (-1.0_f64).exp()).abs() < 0.01, "{}", mean);
+        // The background is not fogged.
+        let away = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, 1));
+        assert_eq!(
+            ray_color(away, &settings, &world, &no_lights(), 10, &mut rng),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(fog.albedo(), 0.0);
+    }
+
+    #[test]
+    fn fog_scatters_light() {
+        // Fog glows in front of a black wall under a white sky.
+        let mut world = HittableList::new();
+        world.add(Sphere::new(
+            Point3::new(0, 0, -1001),
+            1000.0,
+            Lambertian::new(Color::default()),
+        ));
+        let settings = RenderSettings {
+            background: Background::Solid(Color::new(1, 1, 1)),
+            fog: Some(Fog {
+                absorption: 0.0,
+                scattering: 0.5,
+            }),
+            ..settings()
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let n = 2000;
+        let mut mean = Color::default();
+        for _ in 0..n {
+            let r = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1));
+            mean += ray_color(r, &settings, &world, &no_lights(), 50, &mut rng) / n as f64;
+        }
+        // Only light scattered before the wall reaches the camera, and
+        // about half of that comes from the sky.
+        assert!(
+            mean.x() > 0.15 && mean.x() < 1.0 - (-0.5_f64).exp(),
+            "{}",
+            mean
+        );
+    }
+
+    #[test]
+    fn light_sampling_converges_faster() {
+        // A grey floor lit only by a small quad light above it.
+        let light: Arc<dyn Hittable> = Arc::new(Quad::xz(
+            -0.25,
+            0.25,
+            -0.25,
+            0.25,
+            1.0,
+            DiffuseLight::new(Color::new(4, 4, 4)),
+        ));
+        let mut world = HittableList::new();
+        world.add(Quad::xz(
+            -10.0,
+            10.0,
+            -10.0,
+            10.0,
+            0.0,
+            Lambertian::new(Color::new(0.5, 0.5, 0.5)),
+        ));
+        world.add(light.clone());
+        let mut lights = HittableList::new();
+        lights.add(light);
+        let settings = RenderSettings {
+            background: Background::Solid(Color::default()),
+            ..settings()
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let n = 20_000;
+        let mut estimate = |lights: &HittableList| {
+            let (mut sum, mut sum_squared) = (0.0, 0.0);
+            for _ in 0..n {
+                let r = Ray::new(Point3::new(0, 0.5, 1), Vec3::new(0, -0.5, -1));
+                let c = ray_color(r, &settings, &world, lights, 5, &mut rng).x();
+                sum += c;
+                sum_squared += c * c;
+            }
+            let mean = sum / n as f64;
+            (mean, sum_squared / n as f64 - mean * mean)
+        };
+        let (bounced, bounced_variance) = estimate(&no_lights());
+        let (sampled, sampled_variance) = estimate(&lights);
+        // Irradiance under the center of a square of half-side a at height
+        // one: 4 u atan u in units of its radiance, with u = a / √(a² + 1).
+        let u = 0.25 / 1.0625_f64.sqrt();
+        let expected = 0.5 / std::f64::consts::PI * 4.0 * (4.0 * u * u.atan());
+        assert!((sampled - expected).abs() < 0.01, "{}", sampled);
+        assert!((bounced - sampled).abs() < 0.02, "{} {}", bounced, sampled);
+        assert!(sampled_variance * 20.0 < bounced_variance);
+    }
+
+    #[test]
+    fn glossy_light_sampling_is_unbiased() {
+        // A fuzzy metal floor reflecting a quad light: light samples and
+        // reflections both see the light, and their weights add up to one.
+        let light: Arc<dyn Hittable> = Arc::new(Quad::xz(
+            -1.0,
+            1.0,
+            -2.0,
+            0.0,
+            1.0,
+            DiffuseLight::new(Color::new(2, 2, 2)),
+        ));
+        let mut world = HittableList::new();
+        world.add(Quad::xz(
+            -10.0,
+            10.0,
+            -10.0,
+            10.0,
+            0.0,
+            Metal::new(Color::new(0.8, 0.8, 0.8), 0.5),
+        ));
+        world.add(light.clone());
+        let mut lights = HittableList::new();
+        lights.add(light);
+        let settings = RenderSettings {
+            background: Background::Solid(Color::default()),
+            ..settings()
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let n = 40_000;
+        let mut mean = |lights: &HittableList| {
+            let mut sum = 0.0;
+            for _ in 0..n {
+                let r = Ray::new(Point3::new(0, 0.5, 1), Vec3::new(0, -0.5, -1));
+                sum += ray_color(r, &settings, &world, lights, 5, &mut rng).x();
+            }
+            sum / n as f64
+        };
+        let bounced = mean(&no_lights());
+        let sampled = mean(&lights);
+        assert!(bounced > 0.1);
+        assert!(
+            (bounced - sampled).abs() < 0.02 * bounced,
+            "{} {}",
+            bounced,
+            sampled
+        );
+    }
+
+    /// Counts the hit queries made against `inner`.
+    struct Counting<T> {
+        inner: T,
+        queries: AtomicUsize,
+    }
+
+    impl<T: Hittable> Hittable for Counting<T> {
+        fn hit(
+            &self,
+            r: &Ray,
+            t_min: f64,
+            t_max: f64,
+            rec: &mut HitRecord,
+            rng: &mut Box<dyn RngCore>,
+        ) -> bool {
+            self.queries.fetch_add(1, Ordering::Relaxed);
+            self.inner.hit(r, t_min, t_max, rec, rng)
+        }
+
+        fn bounding_box(&self) -> Option<Aabb> {
+            self.inner.bounding_box()
+        }
+    }
+
+    #[test]
+    fn russian_roulette_is_unbiased() {
+        // A small lamp inside a bright closed room, where paths bounce for
+        // a long time.
+        let mut room = HittableList::new();
+        room.add(Sphere::new(
+            Point3::new(0, 0, 0),
+            -5.0,
+            Lambertian::new(Color::new(0.8, 0.8, 0.8)),
+        ));
+        room.add(Sphere::new(
+            Point3::new(0, 3, 0),
+            1.0,
+            DiffuseLight::new(Color::new(1, 1, 1)),
+        ));
+        let world = Counting {
+            inner: room,
+            queries: AtomicUsize::new(0),
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let n = 80_000;
+        let mut mean = |roulette_depth: usize| {
+            let settings = RenderSettings {
+                roulette_depth,
+                ..settings()
+            };
+            world.queries.store(0, Ordering::Relaxed);
+            let mut sum = 0.0;
+            for _ in 0..n {
+                let r = Ray::new(Point3::new(0, -2, 0), Vec3::new(0, -1, 0));
+                sum += ray_color(r, &settings, &world, &no_lights(), 100, &mut rng).x();
+            }
+            (sum / n as f64, world.queries.load(Ordering::Relaxed))
+        };
+        let (full, full_queries) = mean(usize::MAX);
+        let (roulette, roulette_queries) = mean(2);
+        assert!(
+            (full - roulette).abs() < 0.03 * full,
+            "{} {}",
+            full,
+            roulette
+        );
+        assert!(roulette_queries * 2 < full_queries);
+    }
+
+    #[test]
+    fn deep_paths_do_not_overflow_the_stack() {
+        // Inside a perfect mirror a path never ends on its own.
+        let mut world = HittableList::new();
+        world.add(Sphere::new(
+            Point3::new(0, 0, 0),
+            -1.0,
+            Metal::new(Color::new(1, 1, 1), 0.0),
+        ));
+        let settings = RenderSettings {
+            roulette_depth: usize::MAX,
+            ..settings()
+        };
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        let r = Ray::new(Point3::new(0, 0, 0), Vec3::new(0.3, 0.4, 1));
+        let color = ray_color(r, &settings, &world, &no_lights(), 200_000, &mut rng);
+        assert_eq!(color, Color::default());
+    }
+}
@@ -0,0 +1,2133 @@
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    bvh::BvhNode,
+    camera::Camera,
+    cone::Cone,
+    constant_medium::ConstantMedium,
+    csg::{CsgDifference, CsgIntersection, CsgUnion},
+    cuboid::Cuboid,
+    cylinder::Cylinder,
+    disk::Disk,
+    grid_medium::GridMedium,
+    hittable::{Hittable, HittableList},
+    mat4::Mat4,
+    material::{
+        dielectric::Dielectric, diffuse_light::DiffuseLight, isotropic::Isotropic,
+        lambertian::Lambertian, metal::Metal, Material,
+    },
+    moving_sphere::MovingSphere,
+    obj::{load_obj, ObjMaterials},
+    paraboloid::Paraboloid,
+    plane::Plane,
+    quad::Quad,
+    render::{Background, Fog, RenderSettings},
+    sdf::{
+        combinators::{Repetition, SmoothSubtraction, SmoothUnion},
+        mandelbulb::Mandelbulb,
+        primitives::{BoxSdf, CapsuleSdf, RoundBoxSdf, SphereSdf, TorusSdf},
+        Sdf, SdfObject,
+    },
+    sphere::Sphere,
+    texture::{
+        checker::Checker, image_texture::ImageTexture, noise::NoiseTexture,
+        solid_color::SolidColor, Texture,
+    },
+    torus::Torus,
+    transform::Transform,
+    triangle::Triangle,
+    vec3::{Color, Point3, Vec3},
+    voxel_grid::VoxelGrid,
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Parse { .. } => None,
+        }
+    }
+}
+
+/// Error pointing at a byte range of the scene source.
+struct Located {
+    span: Range<usize>,
+    message: String,
+}
+
+impl Located {
+    fn new<T>(at: &Spanned<T>, message: String) -> Self {
+        Located {
+            span: at.span(),
+            message,
+        }
+    }
+}
+
+type Triple = [f64; 3];
+
+fn vec3(t: Triple) -> Vec3 {
+    Vec3::new(t[0], t[1], t[2])
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneFile {
+    image: Spanned<ImageConfig>,
+    camera: Spanned<CameraConfig>,
+    /// Defaults to the blue-white sky gradient.
+    background: Option<Spanned<BackgroundConfig>>,
+    /// No fog by default.
+    fog: Option<Spanned<FogConfig>>,
+    #[serde(default)]
+    textures: BTreeMap<String, Spanned<TextureConfig>>,
+    #[serde(default)]
+    materials: BTreeMap<String, Spanned<MaterialConfig>>,
+    #[serde(default)]
+    objects: Vec<Spanned<ObjectConfig>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImageConfig {
+    width: u32,
+    #[serde(default = "default_aspect_ratio")]
+    aspect_ratio: f64,
+    #[serde(default = "default_samples_per_pixel")]
+    samples_per_pixel: usize,
+    #[serde(default = "default_max_depth")]
+    max_depth: usize,
+    #[serde(default = "default_roulette_depth")]
+    roulette_depth: usize,
+    #[serde(default)]
+    seed: u64,
+}
+
+fn default_aspect_ratio() -> f64 {
+    16.0 / 9.0
+}
+
+fn default_samples_per_pixel() -> usize {
+    100
+}
+
+fn default_max_depth() -> usize {
+    50
+}
+
+fn default_roulette_depth() -> usize {
+    5
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraConfig {
+    lookfrom: Triple,
+    lookat: Triple,
+    #[serde(default = "default_vup")]
+    vup: Triple,
+    #[serde(default = "default_vfov")]
+    vfov: f64,
+    #[serde(default)]
+    aperture: f64,
+    /// Defaults to the distance between `lookfrom` and `lookat`.
+    focus_dist: Option<f64>,
+    /// Times the shutter opens and closes; moving objects are blurred over
+    /// that interval.
+    #[serde(default)]
+    shutter_open: f64,
+    #[serde(default)]
+    shutter_close: f64,
+}
+
+fn default_vup() -> Triple {
+    [0.0, 1.0, 0.0]
+}
+
+fn default_vfov() -> f64 {
+    90.0
+}
+
+/// A color given inline or by the name of a texture.
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "a color [r, g, b] or the name of a texture")]
+enum ColorSource {
+    Color(Triple),
+    Texture(String),
+}
+
+/// A number given inline or by the name of a texture.
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "a number or the name of a texture")]
+enum ScalarSource {
+    Value(f64),
+    Texture(String),
+}
+
+impl Default for ScalarSource {
+    fn default() -> Self {
+        ScalarSource::Value(0.0)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureConfig {
+    Solid {
+        color: Triple,
+    },
+    /// 3D checkerboard of cubes of side `scale`.
+    Checker {
+        even: ColorSource,
+        odd: ColorSource,
+        #[serde(default = "default_checker_scale")]
+        scale: f64,
+    },
+    /// Image file, relative to the scene file.
+    Image {
+        path: PathBuf,
+    },
+    /// Procedural Perlin noise pattern; `low` and `high` default to the
+    /// colors of the pattern's preset.
+    Noise {
+        pattern: NoisePatternConfig,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        low: Option<Triple>,
+        high: Option<Triple>,
+    },
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum NoisePatternConfig {
+    Marble,
+    Wood,
+    Clouds,
+}
+
+fn default_noise_scale() -> f64 {
+    1.0
+}
+
+fn default_checker_scale() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialConfig {
+    Lambertian {
+        albedo: ColorSource,
+    },
+    Metal {
+        albedo: ColorSource,
+        #[serde(default)]
+        fuzz: ScalarSource,
+    },
+    Dielectric {
+        ref_idx: f64,
+    },
+    DiffuseLight {
+        emit: ColorSource,
+    },
+    /// Phase function for the inside of a `constant_medium`.
+    Isotropic {
+        albedo: ColorSource,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum BackgroundConfig {
+    Gradient {
+        #[serde(default = "default_horizon")]
+        horizon: Triple,
+        #[serde(default = "default_zenith")]
+        zenith: Triple,
+    },
+    Solid {
+        color: Triple,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FogConfig {
+    #[serde(default)]
+    absorption: f64,
+    #[serde(default)]
+    scattering: f64,
+}
+
+fn default_horizon() -> Triple {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_zenith() -> Triple {
+    [0.5, 0.7, 1.0]
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectConfig {
+    Sphere {
+        center: Triple,
+        radius: f64,
+        material: String,
+    },
+    /// Sphere moving from `center0` at `time0` to `center1` at `time1`.
+    MovingSphere {
+        center0: Triple,
+        center1: Triple,
+        #[serde(default)]
+        time0: f64,
+        #[serde(default = "default_time1")]
+        time1: f64,
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [Triple; 3],
+        material: String,
+    },
+    /// Infinite plane; `normal` points to its front side.
+    Plane {
+        point: Triple,
+        normal: Triple,
+        material: String,
+    },
+    /// Parallelogram with a corner at `corner` and edges `u` and `v`.
+    Quad {
+        corner: Triple,
+        u: Triple,
+        v: Triple,
+        material: String,
+    },
+    Disk {
+        center: Triple,
+        normal: Triple,
+        radius: f64,
+        material: String,
+    },
+    /// Axis-aligned box between two opposite corners.
+    Cuboid {
+        min: Triple,
+        max: Triple,
+        material: String,
+    },
+    /// Cylinder around the Y axis from the origin up to `height`; place it
+    /// with a transform. The quadrics below are closed unless `capped` is
+    /// false.
+    Cylinder {
+        radius: f64,
+        height: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// Cone around the Y axis with its base at the origin and its apex at
+    /// `height`.
+    Cone {
+        radius: f64,
+        height: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// Paraboloid around the Y axis with its vertex at the origin, opening
+    /// up to a rim of `radius` at `height`.
+    Paraboloid {
+        radius: f64,
+        height: f64,
+        #[serde(default = "default_capped")]
+        capped: bool,
+        material: String,
+    },
+    /// Torus around the Y axis, centered on the origin.
+    Torus {
+        major_radius: f64,
+        minor_radius: f64,
+        material: String,
+    },
+    /// Surface of a signed distance field, found by sphere tracing.
+    Sdf {
+        shape: SdfConfig,
+        material: String,
+        #[serde(default = "default_max_steps")]
+        max_steps: usize,
+        #[serde(default = "default_epsilon")]
+        epsilon: f64,
+    },
+    /// Smoke of uniform `density` filling a closed `boundary`, scattering
+    /// light with `material`, usually an isotropic one.
+    ConstantMedium {
+        boundary: Box<ObjectConfig>,
+        density: f64,
+        material: String,
+    },
+    /// Smoke, clouds or fire from a voxel grid file, relative to the scene
+    /// file, stretched over the box from `min` to `max`. Grids with an
+    /// emission channel glow, `emission_scale` times as bright.
+    GridMedium {
+        path: PathBuf,
+        min: Triple,
+        max: Triple,
+        #[serde(default = "default_scale")]
+        density_scale: f64,
+        #[serde(default = "default_scale")]
+        emission_scale: f64,
+        material: String,
+    },
+    /// Wavefront OBJ file, relative to the scene file. `material` replaces
+    /// the materials of the file's MTL libraries.
+    Obj {
+        path: PathBuf,
+        material: Option<String>,
+    },
+    /// Constructive solid geometry on two closed objects: everything in
+    /// either of them, in both, or in `left` but not in `right`.
+    Union {
+        left: Box<ObjectConfig>,
+        right: Box<ObjectConfig>,
+    },
+    Intersection {
+        left: Box<ObjectConfig>,
+        right: Box<ObjectConfig>,
+    },
+    Difference {
+        left: Box<ObjectConfig>,
+        right: Box<ObjectConfig>,
+    },
+    /// Another object placed with an affine transform: `matrix` (given by
+    /// rows) applies first, then `scale`, `rotate` and `translate`.
+    Transform {
+        object: Box<ObjectConfig>,
+        matrix: Option<[[f64; 4]; 4]>,
+        scale: Option<ScaleConfig>,
+        rotate: Option<RotateConfig>,
+        translate: Option<Triple>,
+    },
+}
+
+/// A distance field, built from primitives and combinators nested inside
+/// each other.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum SdfConfig {
+    Sphere {
+        center: Triple,
+        radius: f64,
+    },
+    Box {
+        center: Triple,
+        half_size: Triple,
+    },
+    RoundBox {
+        center: Triple,
+        half_size: Triple,
+        radius: f64,
+    },
+    Torus {
+        center: Triple,
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    Capsule {
+        a: Triple,
+        b: Triple,
+        radius: f64,
+    },
+    SmoothUnion {
+        a: Box<SdfConfig>,
+        b: Box<SdfConfig>,
+        #[serde(default)]
+        k: f64,
+    },
+    /// `a` with `b` carved out of it.
+    SmoothSubtraction {
+        a: Box<SdfConfig>,
+        b: Box<SdfConfig>,
+        #[serde(default)]
+        k: f64,
+    },
+    /// `shape` repeated every `period` along each axis; zero leaves that
+    /// axis alone.
+    Repetition {
+        shape: Box<SdfConfig>,
+        period: Triple,
+    },
+    Mandelbulb {
+        #[serde(default = "default_power")]
+        power: f64,
+        #[serde(default = "default_iterations")]
+        iterations: usize,
+    },
+}
+
+/// A uniform scale factor or one factor per axis.
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "a number or [x, y, z] scale factors")]
+enum ScaleConfig {
+    Uniform(f64),
+    Axes(Triple),
+}
+
+/// Counter-clockwise rotation by `angle` degrees around `axis`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RotateConfig {
+    axis: Triple,
+    angle: f64,
+}
+
+fn default_time1() -> f64 {
+    1.0
+}
+
+fn default_capped() -> bool {
+    true
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_max_steps() -> usize {
+    256
+}
+
+fn default_epsilon() -> f64 {
+    1e-4
+}
+
+fn default_power() -> f64 {
+    8.0
+}
+
+fn default_iterations() -> usize {
+    10
+}
+
+/// A scene loaded from a TOML description: the camera, the render settings
+/// and the world to render.
+pub struct Scene {
+    pub camera: Camera,
+    pub world: Box<dyn Hittable>,
+    /// Spheres and quads of the world that give off light, to be sampled
+    /// directly.
+    pub lights: HittableList<'static>,
+    pub settings: RenderSettings,
+}
+
+impl Scene {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|source| SceneError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Scene::parse(&source, path)
+    }
+
+    /// Builds a scene from `source`. `path` is used in error messages and
+    /// to resolve the files the scene refers to.
+    pub fn parse(source: &str, path: &Path) -> Result<Scene, SceneError> {
+        let to_error = |span: Option<Range<usize>>, message: &str| {
+            let (line, column) = line_column(source, span.map_or(0, |s| s.start));
+            SceneError::Parse {
+                path: path.to_owned(),
+                line,
+                column,
+                message: message.to_owned(),
+            }
+        };
+        let file: SceneFile =
+            toml::from_str(source).map_err(|e| to_error(e.span(), e.message()))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        build(file, dir).map_err(|e| to_error(Some(e.span), &e.message))
+    }
+}
+
+/// 1-based line and column of the byte `offset` in `source`.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+fn build(file: SceneFile, dir: &Path) -> Result<Scene, Located> {
+    let mut settings = build_settings(&file.image)?;
+    if let Some(background) = &file.background {
+        settings.background = build_background(background)?;
+    }
+    if let Some(fog) = &file.fog {
+        settings.fog = Some(build_fog(fog)?);
+    }
+    let camera = build_camera(&file.camera, file.image.get_ref().aspect_ratio)?;
+
+    let mut textures = Textures {
+        configs: &file.textures,
+        dir,
+        built: BTreeMap::new(),
+        in_progress: vec![],
+    };
+    for (name, config) in &file.textures {
+        textures.get(config, name)?;
+    }
+
+    let mut materials: BTreeMap<String, Arc<dyn Material>> = BTreeMap::new();
+    for (name, config) in &file.materials {
+        materials.insert(name.clone(), build_material(name, config, &mut textures)?);
+    }
+
+    let mut bounded = HittableList::new();
+    let mut unbounded = HittableList::new();
+    let mut lights = HittableList::new();
+    for config in &file.objects {
+        let object = build_object(config, config.get_ref(), &materials, dir)?;
+        if is_light(config.get_ref(), &file.materials) {
+            let light: Arc<dyn Hittable> = Arc::from(object);
+            lights.add(light.clone());
+            bounded.add(light);
+        } else if object.bounding_box().is_some() {
+            bounded.add_boxed(object);
+        } else {
+            unbounded.add_boxed(object);
+        }
+    }
+    if !bounded.is_empty() {
+        unbounded.add(BvhNode::new(bounded));
+    }
+
+    Ok(Scene {
+        camera,
+        world: Box::new(unbounded),
+        lights,
+        settings,
+    })
+}
+
+/// Whether `object` is a shape that can be sampled and is made of a
+/// `diffuse_light` material.
+fn is_light(object: &ObjectConfig, materials: &BTreeMap<String, Spanned<MaterialConfig>>) -> bool {
+    let material = match object {
+        ObjectConfig::Sphere { material, .. } | ObjectConfig::Quad { material, .. } => material,
+        _ => return false,
+    };
+    matches!(
+        materials.get(material).map(Spanned::get_ref),
+        Some(MaterialConfig::DiffuseLight { .. })
+    )
+}
+
+fn build_settings(config: &Spanned<ImageConfig>) -> Result<RenderSettings, Located> {
+    let image = config.get_ref();
+    if image.width < 2 {
+        return Err(Located::new(
+            config,
+            "image width must be at least 2".into(),
+        ));
+    }
+    if !(image.aspect_ratio > 0.0 && image.aspect_ratio.is_finite()) {
+        return Err(Located::new(config, "aspect_ratio must be positive".into()));
+    }
+    let image_height = (image.width as f64 / image.aspect_ratio) as u32;
+    if image_height < 2 {
+        return Err(Located::new(
+            config,
+            format!("image height {} is too small", image_height),
+        ));
+    }
+    if image.samples_per_pixel == 0 {
+        return Err(Located::new(
+            config,
+            "samples_per_pixel must be positive".into(),
+        ));
+    }
+    Ok(RenderSettings {
+        image_width: image.width,
+        image_height,
+        samples_per_pixel: image.samples_per_pixel,
+        max_depth: image.max_depth,
+        roulette_depth: image.roulette_depth,
+        seed: image.seed,
+        background: Background::default(),
+        fog: None,
+    })
+}
+
+fn build_background(config: &Spanned<BackgroundConfig>) -> Result<Background, Located> {
+    Ok(match *config.get_ref() {
+        BackgroundConfig::Gradient { horizon, zenith } => Background::Gradient {
+            horizon: check_color(config, "background horizon", horizon)?,
+            zenith: check_color(config, "background zenith", zenith)?,
+        },
+        BackgroundConfig::Solid { color } => {
+            Background::Solid(check_color(config, "background color", color)?)
+        }
+    })
+}
+
+fn build_fog(config: &Spanned<FogConfig>) -> Result<Fog, Located> {
+    let fog = config.get_ref();
+    if fog.absorption < 0.0 || fog.scattering < 0.0 {
+        return Err(Located::new(
+            config,
+            "fog absorption and scattering must not be negative".into(),
+        ));
+    }
+    if fog.absorption + fog.scattering <= 0.0 {
+        return Err(Located::new(
+            config,
+            "fog needs some absorption or scattering".into(),
+        ));
+    }
+    Ok(Fog {
+        absorption: fog.absorption,
+        scattering: fog.scattering,
+    })
+}
+
+fn build_camera(config: &Spanned<CameraConfig>, aspect_ratio: f64) -> Result<Camera, Located> {
+    let camera = config.get_ref();
+    let lookfrom = vec3(camera.lookfrom);
+    let lookat = vec3(camera.lookat);
+    let vup = vec3(camera.vup);
+    if lookfrom == lookat {
+        return Err(Located::new(
+            config,
+            "lookfrom and lookat must differ".into(),
+        ));
+    }
+    if vup.cross(&(lookfrom - lookat)).length_squared() == 0.0 {
+        return Err(Located::new(
+            config,
+            "vup must not be parallel to the view direction".into(),
+        ));
+    }
+    if !(camera.vfov > 0.0 && camera.vfov < 180.0) {
+        return Err(Located::new(
+            config,
+            format!(
+                "vfov must be between 0 and 180 degrees, got {}",
+                camera.vfov
+            ),
+        ));
+    }
+    if camera.aperture < 0.0 {
+        return Err(Located::new(config, "aperture must not be negative".into()));
+    }
+    let focus_dist = camera.focus_dist.unwrap_or((lookfrom - lookat).length());
+    if focus_dist <= 0.0 {
+        return Err(Located::new(config, "focus_dist must be positive".into()));
+    }
+    if camera.shutter_close < camera.shutter_open {
+        return Err(Located::new(
+            config,
+            "shutter_close must not be before shutter_open".into(),
+        ));
+    }
+    Ok(Camera::new(
+        lookfrom,
+        lookat,
+        vup,
+        camera.vfov,
+        aspect_ratio,
+        camera.aperture,
+        focus_dist,
+    )
+    .with_shutter(camera.shutter_open, camera.shutter_close))
+}
+
+/// Converts `color` after checking that none of its channels is negative;
+/// `what` names the value in the error message.
+fn check_color<T>(config: &Spanned<T>, what: &str, color: Triple) -> Result<Color, Located> {
+    if color.iter().any(|c| *c < 0.0) {
+        return Err(Located::new(
+            config,
+            format!("{} must not be negative", what),
+        ));
+    }
+    Ok(vec3(color))
+}
+
+/// Builds the named textures on demand, so that textures can refer to each
+/// other whatever their order in the file.
+struct Textures<'a> {
+    configs: &'a BTreeMap<String, Spanned<TextureConfig>>,
+    dir: &'a Path,
+    built: BTreeMap<String, Arc<dyn Texture>>,
+    /// Textures being built, to catch cycles.
+    in_progress: Vec<String>,
+}
+
+impl<'a> Textures<'a> {
+    /// The texture called `name`, referred to from `at`.
+    fn get<T>(&mut self, at: &Spanned<T>, name: &str) -> Result<Arc<dyn Texture>, Located> {
+        if let Some(texture) = self.built.get(name) {
+            return Ok(texture.clone());
+        }
+        let config = self
+            .configs
+            .get(name)
+            .ok_or_else(|| Located::new(at, format!("unknown texture `{}`", name)))?;
+        if self.in_progress.iter().any(|n| n == name) {
+            return Err(Located::new(
+                config,
+                format!("texture `{}` refers to itself", name),
+            ));
+        }
+        self.in_progress.push(name.to_owned());
+        let texture = self.build(name, config)?;
+        self.in_progress.pop();
+        self.built.insert(name.to_owned(), texture.clone());
+        Ok(texture)
+    }
+
+    fn build(
+        &mut self,
+        name: &str,
+        config: &Spanned<TextureConfig>,
+    ) -> Result<Arc<dyn Texture>, Located> {
+        Ok(match config.get_ref() {
+            TextureConfig::Solid { color } => {
+                let color = check_color(config, &format!("texture `{}`: color", name), *color)?;
+                Arc::new(SolidColor::new(color))
+            }
+            TextureConfig::Checker { even, odd, scale } => {
+                if !(*scale > 0.0 && scale.is_finite()) {
+                    return Err(Located::new(
+                        config,
+                        format!("texture `{}`: scale must be positive", name),
+                    ));
+                }
+                let even = self.color(config, &format!("texture `{}`: even", name), even)?;
+                let odd = self.color(config, &format!("texture `{}`: odd", name), odd)?;
+                Arc::new(Checker::new(even, odd, *scale))
+            }
+            TextureConfig::Image { path } => {
+                let texture = ImageTexture::open(self.dir.join(path))
+                    .map_err(|e| Located::new(config, format!("{}: {}", path.display(), e)))?;
+                Arc::new(texture)
+            }
+            TextureConfig::Noise {
+                pattern,
+                seed,
+                scale,
+                low,
+                high,
+            } => {
+                if !(*scale > 0.0 && scale.is_finite()) {
+                    return Err(Located::new(
+                        config,
+                        format!("texture `{}`: scale must be positive", name),
+                    ));
+                }
+                let preset = match pattern {
+                    NoisePatternConfig::Marble => NoiseTexture::marble(*seed, *scale),
+                    NoisePatternConfig::Wood => NoiseTexture::wood(*seed, *scale),
+                    NoisePatternConfig::Clouds => NoiseTexture::clouds(*seed, *scale),
+                };
+                let low = match low {
+                    Some(low) => check_color(config, &format!("texture `{}`: low", name), *low)?,
+                    None => preset.low(),
+                };
+                let high = match high {
+                    Some(high) => check_color(config, &format!("texture `{}`: high", name), *high)?,
+                    None => preset.high(),
+                };
+                Arc::new(NoiseTexture::new(
+                    preset.pattern(),
+                    *seed,
+                    *scale,
+                    low,
+                    high,
+                ))
+            }
+        })
+    }
+
+    /// Texture for a color parameter; `what` names it in error messages.
+    fn color<T>(
+        &mut self,
+        at: &Spanned<T>,
+        what: &str,
+        source: &ColorSource,
+    ) -> Result<Arc<dyn Texture>, Located> {
+        match source {
+            ColorSource::Color(color) => {
+                Ok(Arc::new(SolidColor::new(check_color(at, what, *color)?)))
+            }
+            ColorSource::Texture(name) => self.get(at, name),
+        }
+    }
+}
+
+fn build_material(
+    name: &str,
+    config: &Spanned<MaterialConfig>,
+    textures: &mut Textures,
+) -> Result<Arc<dyn Material>, Located> {
+    Ok(match config.get_ref() {
+        MaterialConfig::Lambertian { albedo } => {
+            let what = format!("material `{}`: albedo", name);
+            Arc::new(Lambertian::from_texture(
+                textures.color(config, &what, albedo)?,
+            ))
+        }
+        MaterialConfig::Metal { albedo, fuzz } => {
+            let fuzz = match fuzz {
+                ScalarSource::Value(fuzz) => {
+                    if !(0.0..=1.0).contains(fuzz) {
+                        return Err(Located::new(
+                            config,
+                            format!(
+                                "material `{}`: fuzz must be between 0 and 1, got {}",
+                                name, fuzz
+                            ),
+                        ));
+                    }
+                    Arc::new(SolidColor::new(Color::new(*fuzz, *fuzz, *fuzz)))
+                }
+                ScalarSource::Texture(texture) => textures.get(config, texture)?,
+            };
+            let what = format!("material `{}`: albedo", name);
+            Arc::new(Metal::from_textures(
+                textures.color(config, &what, albedo)?,
+                fuzz,
+            ))
+        }
+        MaterialConfig::Dielectric { ref_idx } => {
+            if *ref_idx <= 0.0 {
+                return Err(Located::new(
+                    config,
+                    format!(
+                        "material `{}`: ref_idx must be positive, got {}",
+                        name, ref_idx
+                    ),
+                ));
+            }
+            Arc::new(Dielectric::new(*ref_idx))
+        }
+        MaterialConfig::DiffuseLight { emit } => {
+            let what = format!("material `{}`: emit", name);
+            Arc::new(DiffuseLight::from_texture(
+                textures.color(config, &what, emit)?,
+            ))
+        }
+        MaterialConfig::Isotropic { albedo } => {
+            let what = format!("material `{}`: albedo", name);
+            Arc::new(Isotropic::from_texture(
+                textures.color(config, &what, albedo)?,
+            ))
+        }
+    })
+}
+
+fn lookup_material(
+    config: &Spanned<ObjectConfig>,
+    materials: &BTreeMap<String, Arc<dyn Material>>,
+    name: &str,
+) -> Result<Arc<dyn Material>, Located> {
+    materials
+        .get(name)
+        .cloned()
+        .ok_or_else(|| Located::new(config, format!("unknown material `{}`", name)))
+}
+
+fn check_radius_and_height(
+    config: &Spanned<ObjectConfig>,
+    radius: f64,
+    height: f64,
+) -> Result<(), Located> {
+    if radius <= 0.0 || height <= 0.0 {
+        return Err(Located::new(
+            config,
+            "radius and height must be positive".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Builds the distance field `shape` nested in `config`; errors point at
+/// `config`.
+fn build_sdf(config: &Spanned<ObjectConfig>, shape: &SdfConfig) -> Result<Arc<dyn Sdf>, Located> {
+    let positive = |value: f64, message: &str| {
+        if value > 0.0 {
+            Ok(())
+        } else {
+            Err(Located::new(config, message.into()))
+        }
+    };
+    let positive_size = |half_size: &Triple| {
+        let smallest = half_size[0].min(half_size[1]).min(half_size[2]);
+        positive(smallest, "box half_size must be positive")
+    };
+    Ok(match shape {
+        SdfConfig::Sphere { center, radius } => {
+            positive(*radius, "sphere radius must be positive")?;
+            Arc::new(SphereSdf::new(vec3(*center), *radius))
+        }
+        SdfConfig::Box { center, half_size } => {
+            positive_size(half_size)?;
+            Arc::new(BoxSdf::new(vec3(*center), vec3(*half_size)))
+        }
+        SdfConfig::RoundBox {
+            center,
+            half_size,
+            radius,
+        } => {
+            positive_size(half_size)?;
+            if *radius < 0.0 {
+                return Err(Located::new(
+                    config,
+                    "rounding radius must not be negative".into(),
+                ));
+            }
+            Arc::new(RoundBoxSdf::new(vec3(*center), vec3(*half_size), *radius))
+        }
+        SdfConfig::Torus {
+            center,
+            major_radius,
+            minor_radius,
+        } => {
+            if *minor_radius <= 0.0 || major_radius < minor_radius {
+                return Err(Located::new(
+                    config,
+                    "torus radii must be positive, the minor one no larger than the major one"
+                        .into(),
+                ));
+            }
+            Arc::new(TorusSdf::new(vec3(*center), *major_radius, *minor_radius))
+        }
+        SdfConfig::Capsule { a, b, radius } => {
+            positive(*radius, "capsule radius must be positive")?;
+            Arc::new(CapsuleSdf::new(vec3(*a), vec3(*b), *radius))
+        }
+        SdfConfig::SmoothUnion { a, b, k } | SdfConfig::SmoothSubtraction { a, b, k } => {
+            if *k < 0.0 {
+                return Err(Located::new(config, "k must not be negative".into()));
+            }
+            let (a, b) = (build_sdf(config, a)?, build_sdf(config, b)?);
+            if let SdfConfig::SmoothUnion { .. } = shape {
+                Arc::new(SmoothUnion::new(a, b, *k))
+            } else {
+                Arc::new(SmoothSubtraction::new(a, b, *k))
+            }
+        }
+        SdfConfig::Repetition { shape, period } => {
+            if period.iter().any(|p| *p < 0.0) {
+                return Err(Located::new(
+                    config,
+                    "repetition period must not be negative".into(),
+                ));
+            }
+            Arc::new(Repetition::new(build_sdf(config, shape)?, vec3(*period)))
+        }
+        SdfConfig::Mandelbulb { power, iterations } => {
+            if *power < 2.0 {
+                return Err(Located::new(
+                    config,
+                    "mandelbulb power must be at least 2".into(),
+                ));
+            }
+            Arc::new(Mandelbulb::new(*power, *iterations))
+        }
+    })
+}
+
+/// Builds one operand of a CSG object, which must be closed.
+fn build_operand(
+    config: &Spanned<ObjectConfig>,
+    object: &ObjectConfig,
+    materials: &BTreeMap<String, Arc<dyn Material>>,
+    dir: &Path,
+) -> Result<Arc<dyn Hittable>, Located> {
+    let operand = build_object(config, object, materials, dir)?;
+    if !operand.is_closed() {
+        return Err(Located::new(
+            config,
+            "CSG operands must be closed objects".into(),
+        ));
+    }
+    Ok(Arc::from(operand))
+}
+
+/// Builds `object`, which is `config` itself or an object nested in it;
+/// errors point at `config`.
+fn build_object(
+    config: &Spanned<ObjectConfig>,
+    object: &ObjectConfig,
+    materials: &BTreeMap<String, Arc<dyn Material>>,
+    dir: &Path,
+) -> Result<Box<dyn Hittable>, Located> {
+    Ok(match object {
+        ObjectConfig::Sphere {
+            center,
+            radius,
+            material,
+        } => {
+            if *radius == 0.0 {
+                return Err(Located::new(
+                    config,
+                    "sphere radius must not be zero".into(),
+                ));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Sphere::new(
+                Point3::new(center[0], center[1], center[2]),
+                *radius,
+                material,
+            ))
+        }
+        ObjectConfig::MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
+        } => {
+            if *radius == 0.0 {
+                return Err(Located::new(
+                    config,
+                    "sphere radius must not be zero".into(),
+                ));
+            }
+            if time1 <= time0 {
+                return Err(Located::new(config, "time1 must be after time0".into()));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(MovingSphere::new(
+                vec3(*center0),
+                vec3(*center1),
+                *time0,
+                *time1,
+                *radius,
+                material,
+            ))
+        }
+        ObjectConfig::Triangle { vertices, material } => {
+            let [v0, v1, v2] = vertices.map(vec3);
+            if (v1 - v0).cross(&(v2 - v0)).length_squared() == 0.0 {
+                return Err(Located::new(config, "triangle is degenerate".into()));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Triangle::new(v0, v1, v2, material))
+        }
+        ObjectConfig::Plane {
+            point,
+            normal,
+            material,
+        } => {
+            let normal = vec3(*normal);
+            if normal.length_squared() == 0.0 {
+                return Err(Located::new(config, "plane normal must not be zero".into()));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Plane::new(vec3(*point), normal, material))
+        }
+        ObjectConfig::Quad {
+            corner,
+            u,
+            v,
+            material,
+        } => {
+            let (u, v) = (vec3(*u), vec3(*v));
+            if u.cross(&v).length_squared() == 0.0 {
+                return Err(Located::new(config, "quad is degenerate".into()));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Quad::new(vec3(*corner), u, v, material))
+        }
+        ObjectConfig::Disk {
+            center,
+            normal,
+            radius,
+            material,
+        } => {
+            let normal = vec3(*normal);
+            if normal.length_squared() == 0.0 {
+                return Err(Located::new(config, "disk normal must not be zero".into()));
+            }
+            if *radius <= 0.0 {
+                return Err(Located::new(config, "disk radius must be positive".into()));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Disk::new(vec3(*center), normal, *radius, material))
+        }
+        ObjectConfig::Cuboid { min, max, material } => {
+            if (0..3).any(|i| min[i] >= max[i]) {
+                return Err(Located::new(
+                    config,
+                    "cuboid min must be below max on every axis".into(),
+                ));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Cuboid::new(vec3(*min), vec3(*max), material))
+        }
+        ObjectConfig::Cylinder {
+            radius,
+            height,
+            capped,
+            material,
+        } => {
+            check_radius_and_height(config, *radius, *height)?;
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Cylinder::new(*radius, *height, *capped, material))
+        }
+        ObjectConfig::Cone {
+            radius,
+            height,
+            capped,
+            material,
+        } => {
+            check_radius_and_height(config, *radius, *height)?;
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Cone::new(*radius, *height, *capped, material))
+        }
+        ObjectConfig::Paraboloid {
+            radius,
+            height,
+            capped,
+            material,
+        } => {
+            check_radius_and_height(config, *radius, *height)?;
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Paraboloid::new(*radius, *height, *capped, material))
+        }
+        ObjectConfig::Torus {
+            major_radius,
+            minor_radius,
+            material,
+        } => {
+            if *minor_radius <= 0.0 || major_radius < minor_radius {
+                return Err(Located::new(
+                    config,
+                    "torus radii must be positive, the minor one no larger than the major one"
+                        .into(),
+                ));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(Torus::new(*major_radius, *minor_radius, material))
+        }
+        ObjectConfig::Sdf {
+            shape,
+            material,
+            max_steps,
+            epsilon,
+        } => {
+            if *max_steps == 0 {
+                return Err(Located::new(config, "max_steps must be positive".into()));
+            }
+            if *epsilon <= 0.0 {
+                return Err(Located::new(config, "epsilon must be positive".into()));
+            }
+            let sdf = build_sdf(config, shape)?;
+            let material = lookup_material(config, materials, material)?;
+            Box::new(
+                SdfObject::shared(sdf, material)
+                    .with_max_steps(*max_steps)
+                    .with_epsilon(*epsilon),
+            )
+        }
+        ObjectConfig::ConstantMedium {
+            boundary,
+            density,
+            material,
+        } => {
+            if *density <= 0.0 {
+                return Err(Located::new(
+                    config,
+                    "medium density must be positive".into(),
+                ));
+            }
+            let boundary = build_object(config, boundary, materials, dir)?;
+            if !boundary.is_closed() {
+                return Err(Located::new(
+                    config,
+                    "the boundary of a medium must be a closed object".into(),
+                ));
+            }
+            let material = lookup_material(config, materials, material)?;
+            Box::new(ConstantMedium::shared(
+                Arc::from(boundary),
+                *density,
+                material,
+            ))
+        }
+        ObjectConfig::GridMedium {
+            path,
+            min,
+            max,
+            density_scale,
+            emission_scale,
+            material,
+        } => {
+            if (0..3).any(|axis| min[axis] >= max[axis]) {
+                return Err(Located::new(
+                    config,
+                    "grid min must be below max on every axis".into(),
+                ));
+            }
+            if *density_scale <= 0.0 {
+                return Err(Located::new(
+                    config,
+                    "density_scale must be positive".into(),
+                ));
+            }
+            if *emission_scale < 0.0 {
+                return Err(Located::new(
+                    config,
+                    "emission_scale must not be negative".into(),
+                ));
+            }
+            let grid =
+                VoxelGrid::load(dir.join(path)).map_err(|e| Located::new(config, e.to_string()))?;
+            let material = lookup_material(config, materials, material)?;
+            Box::new(
+                GridMedium::new(grid, vec3(*min), vec3(*max), *density_scale, material)
+                    .with_emission_scale(*emission_scale),
+            )
+        }
+        ObjectConfig::Obj { path, material } => {
+            let obj_materials = match material {
+                Some(name) => ObjMaterials::Override(lookup_material(config, materials, name)?),
+                None => ObjMaterials::FromMtl(Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.8)))),
+            };
+            let meshes = load_obj(dir.join(path), obj_materials)
+                .map_err(|e| Located::new(config, e.to_string()))?;
+            if meshes.is_empty() {
+                return Err(Located::new(
+                    config,
+                    format!("{} has no faces", path.display()),
+                ));
+            }
+            let mut list = HittableList::new();
+            for obj_mesh in meshes {
+                list.add(obj_mesh.mesh);
+            }
+            Box::new(list)
+        }
+        ObjectConfig::Union { left, right } => {
+            let left = build_operand(config, left, materials, dir)?;
+            let right = build_operand(config, right, materials, dir)?;
+            Box::new(CsgUnion::shared(left, right))
+        }
+        ObjectConfig::Intersection { left, right } => {
+            let left = build_operand(config, left, materials, dir)?;
+            let right = build_operand(config, right, materials, dir)?;
+            Box::new(CsgIntersection::shared(left, right))
+        }
+        ObjectConfig::Difference { left, right } => {
+            let left = build_operand(config, left, materials, dir)?;
+            let right = build_operand(config, right, materials, dir)?;
+            Box::new(CsgDifference::shared(left, right))
+        }
+        ObjectConfig::Transform {
+            object,
+            matrix,
+            scale,
+            rotate,
+            translate,
+        } => {
+            let mut m = matrix.map_or_else(Mat4::identity, Mat4::new);
+            if let Some(scale) = scale {
+                let factors = match scale {
+                    ScaleConfig::Uniform(f) => Vec3::new(*f, *f, *f),
+                    ScaleConfig::Axes(axes) => vec3(*axes),
+                };
+                m = Mat4::scaling(&factors) * m;
+            }
+            if let Some(rotate) = rotate {
+                let axis = vec3(rotate.axis);
+                if axis.length_squared() == 0.0 {
+                    return Err(Located::new(
+                        config,
+                        "rotation axis must not be zero".into(),
+                    ));
+                }
+                m = Mat4::rotation(&axis, rotate.angle) * m;
+            }
+            if let Some(offset) = translate {
+                m = Mat4::translation(&vec3(*offset)) * m;
+            }
+            if m.inverse().is_none() {
+                return Err(Located::new(config, "transform is singular".into()));
+            }
+            let object = build_object(config, object, materials, dir)?;
+            Box::new(Transform::shared(Arc::from(object), m))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Scene, SceneError};
+    use crate::{
+        hittable::{test_rng, HitRecord, Hittable},
+        ray::Ray,
+        render::{Background, Fog},
+        vec3::{Color, Point3, Vec3},
+        voxel_grid::VoxelGrid,
+    };
+
+    const SCENE: &str = r#"
+[image]
+width = 64
+aspect_ratio = 2.0
+samples_per_pixel = 8
+
+[camera]
+lookfrom = [0, 0, 0]
+lookat = [0, 0, -1]
+
+[materials.ground]
+type = "lambertian"
+albedo = [0.8, 0.8, 0.0]
+
+[materials.chrome]
+type = "metal"
+albedo = [0.8, 0.8, 0.8]
+fuzz = 0.1
+
+[[objects]]
+type = "sphere"
+center = [0, 0, -1]
+radius = 0.5
+material = "chrome"
+
+[[objects]]
+type = "sphere"
+center = [0, -100.5, -1]
+radius = 100
+material = "ground"
+"#;
+
+    fn parse(source: &str) -> Result<Scene, SceneError> {
+        Scene::parse(source, Path::new("test.toml"))
+    }
+
+    fn error_line(source: &str) -> (usize, String) {
+        match parse(source) {
+            Err(SceneError::Parse { line, message, .. }) => (line, message),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("scene should not load"),
+        }
+    }
+
+    #[test]
+    fn loads_scene() {
+        let scene = parse(SCENE).unwrap();
+        assert_eq!(scene.settings.image_width, 64);
+        assert_eq!(scene.settings.image_height, 32);
+        assert_eq!(scene.settings.samples_per_pixel, 8);
+        assert_eq!(scene.settings.max_depth, 50);
+        assert_eq!(scene.settings.roulette_depth, 5);
+        assert_eq!(scene.camera.aspect_ratio(), 2.0);
+
+        let r = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1));
+        let mut rec = HitRecord::default();
+        assert!(scene
+            .world
+            .hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 0.5);
+    }
+
+    #[test]
+    fn collects_lights() {
+        let source = format!(
+            "{}{}",
+            SCENE,
+            r#"
+[materials.lamp]
+type = "diffuse_light"
+emit = [4, 4, 4]
+
+[[objects]]
+type = "sphere"
+center = [0, 3, -1]
+radius = 0.25
+material = "lamp"
+
+[[objects]]
+type = "quad"
+corner = [-1, 2, -2]
+u = [2, 0, 0]
+v = [0, 0, 2]
+material = "lamp"
+
+[[objects]]
+type = "triangle"
+vertices = [[5, 0, 0], [6, 0, 0], [5, 1, 0]]
+material = "lamp"
+"#
+        );
+        let scene = parse(&source).unwrap();
+        // The triangle cannot be sampled, and the chrome sphere is no light.
+        assert_eq!(scene.lights.len(), 2);
+        let origin = Point3::new(0, 0, 0);
+        assert!(scene.lights.pdf_value(&origin, &Vec3::new(0, 1, -0.5)) > 0.0);
+        assert_eq!(scene.lights.pdf_value(&origin, &Vec3::new(0, 0, -1)), 0.0);
+        // The lights are still part of the world.
+        let r = Ray::new(origin, Vec3::new(0, 1, -0.5));
+        let mut rec = HitRecord::default();
+        assert!(scene
+            .world
+            .hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.material.emitted(&rec), Color::new(4, 4, 4));
+        assert!(parse(SCENE).unwrap().lights.is_empty());
+    }
+
+    #[test]
+    fn unknown_material_reports_line() {
+        let source = SCENE.replace("material = \"ground\"", "material = \"grass\"");
+        let (line, message) = error_line(&source);
+        assert_eq!(line, 26);
+        assert_eq!(message, "unknown material `grass`");
+    }
+
+    #[test]
+    fn unknown_material_type_reports_line() {
+        let source = SCENE.replace("type = \"metal\"", "type = \"plastic\"");
+        let (line, message) = error_line(&source);
+        assert_eq!(line, 16);
+        assert!(message.contains("plastic"), "{}", message);
+    }
+
+    #[test]
+    fn bad_value_reports_line() {
+        let source = SCENE.replace("fuzz = 0.1", "fuzz = 2.0");
+        let (line, message) = error_line(&source);
+        assert_eq!(line, 15);
+        assert!(message.contains("fuzz"), "{}", message);
+    }
+
+    #[test]
+    fn wrong_type_reports_line() {
+        let source = SCENE.replace("width = 64", "width = \"wide\"");
+        let (line, _) = error_line(&source);
+        assert_eq!(line, 3);
+    }
+
+    #[test]
+    fn display_includes_location() {
+        let source = SCENE.replace("material = \"ground\"", "material = \"grass\"");
+        let message = parse(&source).err().unwrap().to_string();
+        assert!(message.starts_with("test.toml:26:"), "{}", message);
+    }
+
+    #[test]
+    fn solid_background_and_lights() {
+        let source = format!(
+            "{}\n[background]\ntype = \"solid\"\ncolor = [0, 0, 0]\n\n[materials.lamp]\ntype = \"diffuse_light\"\nemit = [4, 4, 4]\n",
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(
+            scene.settings.background,
+            Background::Solid(Color::default())
+        );
+        assert_eq!(
+            parse(SCENE).unwrap().settings.background,
+            Background::default()
+        );
+    }
+
+    #[test]
+    fn negative_background_reports_line() {
+        let source = format!(
+            "{}\n[background]\ntype = \"solid\"\ncolor = [0, -1, 0]\n",
+            SCENE
+        );
+        let (line, message) = error_line(&source);
+        assert_eq!(line, 32);
+        assert_eq!(message, "background color must not be negative");
+    }
+
+    #[test]
+    fn triangles() {
+        let source = format!(
+            "{}\n[[objects]]\ntype = \"triangle\"\nvertices = [[-1, -1, -3], [1, -1, -3], [0, 1, -3]]\nmaterial = \"ground\"\n",
+            SCENE
+        );
+        parse(&source).unwrap();
+        let degenerate = source.replace("[0, 1, -3]", "[0, -1, -3]");
+        let (line, message) = error_line(&degenerate);
+        assert_eq!(line, 32);
+        assert_eq!(message, "triangle is degenerate");
+    }
+
+    #[test]
+    fn motion_blur() {
+        let source = format!(
+            "{}\n[[objects]]\ntype = \"moving_sphere\"\ncenter0 = [0, 2, -3]\ncenter1 = [1, 2, -3]\nradius = 0.5\nmaterial = \"ground\"\n",
+            SCENE.replace("lookat = [0, 0, -1]", "lookat = [0, 0, -1]\nshutter_close = 0.5")
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(scene.camera.shutter(), (0.0, 0.5));
+
+        let r = Ray::with_time(Point3::new(1, 2, 0), Vec3::new(0, 0, -1), 1.0);
+        let mut rec = HitRecord::default();
+        assert!(scene
+            .world
+            .hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 2.5);
+
+        let (line, message) =
+            error_line(&source.replace("shutter_close = 0.5", "shutter_open = 1"));
+        assert_eq!(line, 7);
+        assert_eq!(message, "shutter_close must not be before shutter_open");
+        let (line, message) =
+            error_line(&source.replace("center1 = [1, 2, -3]", "center1 = [1, 2, -3]\ntime1 = 0"));
+        assert_eq!(line, 33);
+        assert_eq!(message, "time1 must be after time0");
+    }
+
+    #[test]
+    fn transforms() {
+        let source = format!(
+            r#"{}
+[[objects]]
+type = "transform"
+scale = [1, 2, 1]
+rotate = {{ axis = [0, 1, 0], angle = 90 }}
+translate = [0, 0, -10]
+
+[objects.object]
+type = "sphere"
+center = [0, 0, 2]
+radius = 0.5
+material = "ground"
+"#,
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        // The sphere is rotated from +Z to +X, stretched along Y and moved
+        // back.
+        let r = Ray::new(Point3::new(2, 0.9, 0), Vec3::new(0, 0, -1));
+        let mut rec = HitRecord::default();
+        assert!(scene
+            .world
+            .hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!((rec.p.x() - 2.0).abs() < 1e-9 && rec.p.z() > -10.0);
+
+        let (line, message) = error_line(&source.replace("scale = [1, 2, 1]", "scale = [1, 0, 1]"));
+        assert_eq!(line, 32);
+        assert_eq!(message, "transform is singular");
+        let (line, message) = error_line(&source.replace(
+            "radius = 0.5\nmaterial = \"ground\"",
+            "radius = 0.5\nmaterial = \"grass\"",
+        ));
+        assert_eq!(line, 32);
+        assert_eq!(message, "unknown material `grass`");
+    }
+
+    #[test]
+    fn flat_shapes() {
+        let source = format!(
+            r#"{}
+[[objects]]
+type = "plane"
+point = [0, -3, 0]
+normal = [0, 1, 0]
+material = "ground"
+
+[[objects]]
+type = "quad"
+corner = [-1, -1, -3]
+u = [2, 0, 0]
+v = [0, 2, 0]
+material = "ground"
+
+[[objects]]
+type = "disk"
+center = [0, 0, 3]
+normal = [0, 0, 1]
+radius = 1
+material = "ground"
+
+[[objects]]
+type = "cuboid"
+min = [4, 0, 0]
+max = [5, 1, 1]
+material = "chrome"
+"#,
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        let mut rec = HitRecord::default();
+        let down = Ray::new(Point3::new(200, 0, 0), Vec3::new(0, -1, 0));
+        assert!(scene
+            .world
+            .hit(&down, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 3.0);
+        let into_box = Ray::new(Point3::new(4.5, 0.5, 5), Vec3::new(0, 0, -1));
+        assert!(scene
+            .world
+            .hit(&into_box, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 4.0);
+
+        let (line, message) = error_line(&source.replace("v = [0, 2, 0]", "v = [4, 0, 0]"));
+        assert_eq!(line, 38);
+        assert_eq!(message, "quad is degenerate");
+        let (line, message) = error_line(&source.replace("radius = 1\n", "radius = 0\n"));
+        assert_eq!(line, 45);
+        assert_eq!(message, "disk radius must be positive");
+        let (line, message) = error_line(&source.replace("max = [5, 1, 1]", "max = [5, 1, 0]"));
+        assert_eq!(line, 52);
+        assert_eq!(message, "cuboid min must be below max on every axis");
+    }
+
+    #[test]
+    fn quadrics() {
+        let source = format!(
+            r#"{}
+[[objects]]
+type = "cylinder"
+radius = 1
+height = 2
+material = "ground"
+
+[[objects]]
+type = "transform"
+translate = [10, 0, 0]
+
+[objects.object]
+type = "cone"
+radius = 1
+height = 3
+capped = false
+material = "ground"
+
+[[objects]]
+type = "transform"
+translate = [20, 0, 0]
+
+[objects.object]
+type = "paraboloid"
+radius = 1
+height = 4
+material = "ground"
+
+[[objects]]
+type = "torus"
+major_radius = 2
+minor_radius = 0.5
+material = "chrome"
+"#,
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        let down = |x: f64| Ray::new(Point3::new(x, 10, 0), Vec3::new(0, -1, 0));
+        let mut rec = HitRecord::default();
+        for (x, t) in [(0.0, 8.0), (10.5, 8.5), (20.0, 6.0), (2.0, 9.5)] {
+            assert!(scene
+                .world
+                .hit(&down(x), 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+            assert!((rec.t - t).abs() < 1e-9, "{} at {}", rec.t, x);
+        }
+
+        let (line, message) = error_line(&source.replace("height = 2\n", "height = 0\n"));
+        assert_eq!(line, 32);
+        assert_eq!(message, "radius and height must be positive");
+        let (line, message) = error_line(&source.replace("minor_radius = 0.5", "minor_radius = 3"));
+        assert_eq!(line, 59);
+        assert!(message.starts_with("torus radii"), "{}", message);
+    }
+
+    #[test]
+    fn csg() {
+        let source = format!(
+            r#"{}
+[[objects]]
+type = "difference"
+
+[objects.left]
+type = "cuboid"
+min = [199, -1, -11]
+max = [201, 1, -9]
+material = "ground"
+
+[objects.right]
+type = "union"
+
+[objects.right.left]
+type = "sphere"
+center = [201, 0, -10]
+radius = 0.5
+material = "chrome"
+
+[objects.right.right]
+type = "sphere"
+center = [199, 0, -10]
+radius = 0.5
+material = "chrome"
+"#,
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        let mut rec = HitRecord::default();
+        let into_bite = Ray::new(Point3::new(210, 0, -10), Vec3::new(-1, 0, 0));
+        assert!(scene
+            .world
+            .hit(&into_bite, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 9.5);
+        assert!(rec.front_face);
+        let down = Ray::new(Point3::new(200, 10, -10), Vec3::new(0, -1, 0));
+        assert!(scene
+            .world
+            .hit(&down, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert_eq!(rec.t, 9.0);
+
+        let (line, message) = error_line(&source.replace(
+            "type = \"sphere\"\ncenter = [199, 0, -10]\nradius = 0.5",
+            "type = \"disk\"\ncenter = [199, 0, -10]\nnormal = [1, 0, 0]\nradius = 0.5",
+        ));
+        assert_eq!(line, 32);
+        assert_eq!(message, "CSG operands must be closed objects");
+    }
+
+    #[test]
+    fn sdf_objects() {
+        let source = format!(
+            r#"{}
+[[objects]]
+type = "sdf"
+material = "ground"
+epsilon = 1e-6
+
+[objects.shape]
+type = "smooth_subtraction"
+k = 0
+
+[objects.shape.a]
+type = "round_box"
+center = [200, 0, -10]
+half_size = [1, 1, 1]
+radius = 0.2
+
+[objects.shape.b]
+type = "sphere"
+center = [201, 0, -10]
+radius = 0.75
+
+[[objects]]
+type = "sdf"
+material = "chrome"
+shape = {{ type = "mandelbulb", iterations = 6 }}
+"#,
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        let mut rec = HitRecord::default();
+        let into_bite = Ray::new(Point3::new(210, 0, -10), Vec3::new(-1, 0, 0));
+        assert!(scene
+            .world
+            .hit(&into_bite, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!((rec.t - 9.75).abs() < 1e-5, "{}", rec.t);
+        let down = Ray::new(Point3::new(200, 10, -10), Vec3::new(0, -1, 0));
+        assert!(scene
+            .world
+            .hit(&down, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!((rec.t - 9.0).abs() < 1e-5, "{}", rec.t);
+
+        let (line, message) = error_line(&source.replace("radius = 0.75", "radius = 0"));
+        assert_eq!(line, 32);
+        assert_eq!(message, "sphere radius must be positive");
+        let (line, message) = error_line(&source.replace("epsilon = 1e-6", "epsilon = 0"));
+        assert_eq!(line, 32);
+        assert_eq!(message, "epsilon must be positive");
+        let (line, message) = error_line(&source.replace("iterations = 6", "power = 1"));
+        assert_eq!(line, 52);
+        assert_eq!(message, "mandelbulb power must be at least 2");
+    }
+
+    #[test]
+    fn media_and_fog() {
+        let source = format!(
+            r#"{}
+[fog]
+absorption = 0.01
+scattering = 0.02
+
+[materials.smoke]
+type = "isotropic"
+albedo = [0.5, 0.5, 0.5]
+
+[[objects]]
+type = "constant_medium"
+density = 1e6
+material = "smoke"
+
+[objects.boundary]
+type = "cuboid"
+min = [199, -1, -11]
+max = [201, 1, -9]
+material = "ground"
+"#,
+            SCENE
+        );
+        let scene = parse(&source).unwrap();
+        assert_eq!(
+            scene.settings.fog,
+            Some(Fog {
+                absorption: 0.01,
+                scattering: 0.02
+            })
+        );
+        assert_eq!(parse(SCENE).unwrap().settings.fog, None);
+        let mut rec = HitRecord::default();
+        let into_smoke = Ray::new(Point3::new(200, 0, 0), Vec3::new(0, 0, -1));
+        assert!(scene
+            .world
+            .hit(&into_smoke, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!((rec.t - 9.0).abs() < 1e-5, "{}", rec.t);
+
+        let (line, message) = error_line(&source.replace("scattering = 0.02", "scattering = -1"));
+        assert_eq!(line, 32);
+        assert_eq!(
+            message,
+            "fog absorption and scattering must not be negative"
+        );
+        let (line, message) = error_line(&source.replace("density = 1e6", "density = 0"));
+        assert_eq!(line, 40);
+        assert_eq!(message, "medium density must be positive");
+        let (line, message) = error_line(&source.replace(
+            "type = \"cuboid\"\nmin = [199, -1, -11]\nmax = [201, 1, -9]",
+            "type = \"disk\"\ncenter = [200, 0, -10]\nnormal = [0, 0, 1]\nradius = 1",
+        ));
+        assert_eq!(line, 40);
+        assert_eq!(message, "the boundary of a medium must be a closed object");
+    }
+
+    #[test]
+    fn grid_media() {
+        let dir = std::env::temp_dir().join(format!("raytracer-grid-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let grid = VoxelGrid::new([1, 1, 1], vec![1e6]);
+        std::fs::write(dir.join("cloud.grid"), grid.to_bytes()).unwrap();
+        std::fs::write(dir.join("bad.grid"), b"grid 1 1 1 density\n").unwrap();
+        let source = format!(
+            r#"{}
+[materials.cloud]
+type = "isotropic"
+albedo = [0.9, 0.9, 0.9]
+
+[[objects]]
+type = "grid_medium"
+path = "cloud.grid"
+min = [199, -1, -11]
+max = [201, 1, -9]
+density_scale = 2
+material = "cloud"
+"#,
+            SCENE
+        );
+        let path = dir.join("scene.toml");
+        let scene = Scene::parse(&source, &path).unwrap();
+        let mut rec = HitRecord::default();
+        let into_cloud = Ray::new(Point3::new(200, 0, 0), Vec3::new(0, 0, -1));
+        assert!(scene
+            .world
+            .hit(&into_cloud, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!((rec.t - 9.0).abs() < 1e-5, "{}", rec.t);
+
+        let error = |source: &str| match Scene::parse(source, &path) {
+            Err(SceneError::Parse { line, message, .. }) => (line, message),
+            _ => panic!("scene should not load"),
+        };
+        let (line, message) = error(&source.replace("cloud.grid", "bad.grid"));
+        assert_eq!(line, 36);
+        assert!(
+            message.ends_with("bad.grid: expected 4 bytes of voxel data, found 0"),
+            "{}",
+            message
+        );
+        let (line, message) = error(&source.replace("max = [201, 1, -9]", "max = [201, -1, -9]"));
+        assert_eq!(line, 36);
+        assert_eq!(message, "grid min must be below max on every axis");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn obj_objects() {
+        let dir = std::env::temp_dir().join(format!("raytracer-scene-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("tri.obj"),
+            "v -1 -1 -3\nv 1 -1 -3\nv 0 1 -3\nf 1 2 3\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("bad.obj"), "v -1 -1 -3\nf 1 2 3\n").unwrap();
+        let source = format!(
+            "{}\n[[objects]]\ntype = \"obj\"\npath = \"tri.obj\"\nmaterial = \"ground\"\n",
+            SCENE
+        );
+        let path = dir.join("scene.toml");
+        Scene::parse(&source, &path).unwrap();
+
+        match Scene::parse(&source.replace("tri.obj", "bad.obj"), &path) {
+            Err(SceneError::Parse { line, message, .. }) => {
+                assert_eq!(line, 32);
+                assert!(message.contains("bad.obj:2:"), "{}", message);
+            }
+            _ => panic!("bad.obj should not load"),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn textures() {
+        let source = format!(
+            r#"{}
+[textures.checks]
+type = "checker"
+even = "white"
+odd = [0.1, 0.1, 0.1]
+scale = 0.25
+
+[textures.white]
+type = "solid"
+color = [0.9, 0.9, 0.9]
+
+[materials.floor]
+type = "lambertian"
+albedo = "checks"
+
+[materials.brushed]
+type = "metal"
+albedo = [0.8, 0.8, 0.8]
+fuzz = "checks"
+"#,
+            SCENE
+        );
+        parse(&source).unwrap();
+
+        let unknown = source.replace("albedo = \"checks\"", "albedo = \"stripes\"");
+        let (line, message) = error_line(&unknown);
+        assert_eq!(line, 42);
+        assert_eq!(message, "unknown texture `stripes`");
+
+        let cycle = source.replace("even = \"white\"", "even = \"checks\"");
+        let (line, message) = error_line(&cycle);
+        assert_eq!(line, 32);
+        assert_eq!(message, "texture `checks` refers to itself");
+
+        let wrong_type = source.replace("odd = [0.1, 0.1, 0.1]", "odd = 3");
+        let (_, message) = error_line(&wrong_type);
+        assert!(
+            message.contains("a color [r, g, b] or the name of a texture"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn noise_textures() {
+        let source = format!(
+            "{}\n[textures.marble]\ntype = \"noise\"\npattern = \"marble\"\nseed = 4\nscale = 3\nhigh = [1, 1, 1]\n",
+            SCENE
+        );
+        parse(&source).unwrap();
+        let (line, message) = error_line(&source.replace("\"marble\"\n", "\"granite\"\n"));
+        assert_eq!(line, 32);
+        assert!(message.contains("granite"), "{}", message);
+        let (line, message) = error_line(&source.replace("scale = 3", "scale = 0"));
+        assert_eq!(line, 32);
+        assert_eq!(message, "texture `marble`: scale must be positive");
+    }
+
+    #[test]
+    fn image_textures() {
+        let dir = std::env::temp_dir().join(format!("raytracer-texture-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        image::RgbImage::from_pixel(2, 2, image::Rgb([200, 100, 0]))
+            .save(dir.join("wood.png"))
+            .unwrap();
+        let source = format!(
+            "{}
+[textures.wood]
+type = \"image\"
+path = \"wood.png\"
+",
+            SCENE
+        );
+        let path = dir.join("scene.toml");
+        Scene::parse(&source, &path).unwrap();
+
+        match Scene::parse(&source.replace("wood.png", "missing.png"), &path) {
+            Err(SceneError::Parse { line, message, .. }) => {
+                assert_eq!(line, 32);
+                assert!(message.starts_with("missing.png:"), "{}", message);
+            }
+            _ => panic!("missing.png should not load"),
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(matches!(
+            Scene::load("does/not/exist.toml"),
+            Err(SceneError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn bundled_scenes_load() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("toml") {
+                continue;
+            }
+            if let Err(e) = Scene::load(&path) {
+                panic!("{}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+pub mod combinators;
+pub mod mandelbulb;
+pub mod primitives;
+
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    sphere::sphere_uv,
+    vec3::{Point3, Vec3},
+};
+
+/// Signed distance function: the distance from a point to a surface,
+/// negative inside it. Estimates may fall short of the true distance but
+/// must never exceed it, or sphere tracing steps through the surface.
+pub trait Sdf: Sync + Send {
+    fn distance(&self, p: &Point3) -> f64;
+    /// Box enclosing the surface, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+}
+
+impl Sdf for Arc<dyn Sdf> {
+    fn distance(&self, p: &Point3) -> f64 {
+        self.as_ref().distance(p)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.as_ref().bounding_box()
+    }
+}
+
+/// Renders the zero set of a signed distance function by sphere tracing:
+/// the ray advances by the distance to the surface until it is closer than
+/// `epsilon`, giving up after `max_steps`. Normals are the central-difference
+/// gradient of the distance; `u` and `v` are the spherical coordinates of
+/// the normal, as on a sphere.
+pub struct SdfObject {
+    sdf: Arc<dyn Sdf>,
+    max_steps: usize,
+    epsilon: f64,
+    material: Box<dyn Material + 'static>,
+}
+
+impl SdfObject {
+    pub fn new<S: Sdf + 'static, M: Material + 'static>(sdf: S, material: M) -> Self {
+        Self::shared(Arc::new(sdf), material)
+    }
+
+    pub fn shared<M: Material + 'static>(sdf: Arc<dyn Sdf>, material: M) -> Self {
+        SdfObject {
+            sdf,
+            max_steps: 256,
+            epsilon: 1e-4,
+            material: Box::new(material),
+        }
+    }
+
+    /// Most steps marched along one ray before it counts as a miss.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Distance to the surface at which the march counts as a hit, also
+    /// used as the central-difference step of the normals.
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn max_steps(&self) -> usize {
+        self.max_steps
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// Outward normal at `p`, from central differences of the distance.
+    pub fn normal(&self, p: &Point3) -> Vec3 {
+        let h = self.epsilon;
+        let axis =
+            |offset: Vec3| self.sdf.distance(&(p + &offset)) - self.sdf.distance(&(p - &offset));
+        Vec3::new(
+            axis(Vec3::new(h, 0, 0)),
+            axis(Vec3::new(0, h, 0)),
+            axis(Vec3::new(0, 0, h)),
+        )
+        .unit()
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let (mut start, mut end) = (t_min, t_max);
+        if let Some(bbox) = self.sdf.bounding_box() {
+            // Only march where the ray crosses the box of the surface.
+            let bbox = bbox.expanded(4.0 * self.epsilon);
+            match bbox.clip(r, t_min, t_max) {
+                Some((t0, t1)) => (start, end) = (t0, t1),
+                None => return false,
+            }
+        }
+        // March in distances along the unit direction; `t` is in units of
+        // the ray direction, which transforms do not normalize.
+        let length = r.direction().length();
+        let d = r.direction() / length;
+        let mut s = start * length;
+        let end = end * length;
+        // A ray leaving the surface it starts on must first get clear of it.
+        let mut leaving = true;
+        for _ in 0..self.max_steps {
+            if s >= end {
+                return false;
+            }
+            let p = r.origin() + &(d * s);
+            let distance = self.sdf.distance(&p).abs();
+            if distance < self.epsilon {
+                if !leaving {
+                    let t = s / length;
+                    if t <= t_min {
+                        return false;
+                    }
+                    rec.t = t;
+                    rec.p = r.at(t);
+                    let normal = self.normal(&p);
+                    rec.set_face_normal(r, &normal);
+                    (rec.u, rec.v) = sphere_uv(&normal);
+                    rec.material = self.material.clone_box();
+                    return true;
+                }
+                s += self.epsilon;
+            } else {
+                leaving = false;
+                s += distance;
+            }
+        }
+        return false;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.sdf.bounding_box()?.expanded(4.0 * self.epsilon))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{primitives::SphereSdf, SdfObject};
+    use crate::{
+        hittable::hit,
+        mat4::Mat4,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        transform::Transform,
+        vec3::{Point3, Vec3},
+    };
+
+    fn ball() -> SdfObject {
+        SdfObject::new(
+            SphereSdf::new(Point3::new(0, 0, -3), 1.0),
+            Lambertian::default(),
+        )
+        .with_epsilon(1e-6)
+    }
+
+    #[test]
+    fn traces_a_sphere() {
+        let ball = ball();
+        let rec = hit(&ball, &Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1))).unwrap();
+        assert!((rec.t - 2.0).abs() < 1e-5);
+        assert!((rec.normal - Vec3::new(0, 0, 1)).length() < 1e-5);
+        assert!(rec.front_face);
+        let rec = hit(
+            &ball,
+            &Ray::new(Point3::new(0.6, 0, 0), Vec3::new(0, 0, -1)),
+        )
+        .unwrap();
+        assert!((rec.t - 2.2).abs() < 1e-5);
+        assert!((rec.normal - Vec3::new(0.6, 0, 0.8)).length() < 1e-5);
+        assert!(hit(
+            &ball,
+            &Ray::new(Point3::new(1.1, 0, 0), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn leaves_the_surface_it_starts_on() {
+        let ball = ball();
+        // Reflected off the front of the sphere: nothing else to hit.
+        let r = Ray::new(Point3::new(0, 0, -2), Vec3::new(0, 0, 1));
+        assert!(hit(&ball, &r).is_none());
+        // Refracted into it: the next hit is the back, from inside.
+        let r = Ray::new(Point3::new(0, 0, -2), Vec3::new(0, 0, -1));
+        let rec = hit(&ball, &r).unwrap();
+        assert!((rec.t - 2.0).abs() < 1e-5);
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn step_budget() {
+        // Grazing rays crawl along the surface and run out of steps.
+        let r = Ray::new(Point3::new(1.0 + 1e-3, 0, 0), Vec3::new(0, 0, -1));
+        assert!(hit(&ball(), &r).is_none());
+        assert!(hit(&ball().with_max_steps(10_000), &r).is_none());
+        let r = Ray::new(Point3::new(0.999, 0, 0), Vec3::new(0, 0, -1));
+        assert!(hit(&ball().with_max_steps(10_000), &r).is_some());
+        assert!(hit(&ball().with_max_steps(2), &r).is_none());
+    }
+
+    #[test]
+    fn placed_with_transform() {
+        // Rays are not unit length in the space of the object.
+        let big = Transform::new(ball(), Mat4::scaling(&Vec3::new(2, 2, 2)));
+        let rec = hit(&big, &Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1))).unwrap();
+        assert!((rec.t - 4.0).abs() < 1e-4);
+        assert!((rec.p - Point3::new(0, 0, -4)).length() < 1e-4);
+    }
+}
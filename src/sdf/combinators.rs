@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    sdf::Sdf,
+    vec3::{Point3, Vec3},
+};
+
+/// Union of two surfaces with a fillet of size `k` where they meet, using
+/// the polynomial smooth minimum. With `k` zero it is the plain union.
+pub struct SmoothUnion {
+    a: Arc<dyn Sdf>,
+    b: Arc<dyn Sdf>,
+    k: f64,
+}
+
+impl SmoothUnion {
+    pub fn new(a: Arc<dyn Sdf>, b: Arc<dyn Sdf>, k: f64) -> Self {
+        SmoothUnion {
+            a,
+            b,
+            k: k.max(0.0),
+        }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, p: &Point3) -> f64 {
+        let (a, b) = (self.a.distance(p), self.b.distance(p));
+        if self.k == 0.0 {
+            return a.min(b);
+        }
+        let h = (0.5 + 0.5 * (b - a) / self.k).clamp(0.0, 1.0);
+        return b + (a - b) * h - self.k * h * (1.0 - h);
+    }
+
+    /// The fillet reaches at most `k / 4` beyond the two surfaces.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let both = self.a.bounding_box()?.surrounding(&self.b.bounding_box()?);
+        Some(both.expanded(self.k / 4.0))
+    }
+}
+
+/// Surface `a` with `b` carved out of it, rounded by `k` along the cut.
+/// With `k` zero it is the plain subtraction.
+pub struct SmoothSubtraction {
+    a: Arc<dyn Sdf>,
+    b: Arc<dyn Sdf>,
+    k: f64,
+}
+
+impl SmoothSubtraction {
+    pub fn new(a: Arc<dyn Sdf>, b: Arc<dyn Sdf>, k: f64) -> Self {
+        SmoothSubtraction {
+            a,
+            b,
+            k: k.max(0.0),
+        }
+    }
+}
+
+impl Sdf for SmoothSubtraction {
+    fn distance(&self, p: &Point3) -> f64 {
+        let (a, b) = (self.a.distance(p), self.b.distance(p));
+        if self.k == 0.0 {
+            return a.max(-b);
+        }
+        let h = (0.5 - 0.5 * (a + b) / self.k).clamp(0.0, 1.0);
+        return a + (-b - a) * h + self.k * h * (1.0 - h);
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.a.bounding_box()
+    }
+}
+
+/// Copies of a surface repeated every `period` along each axis, without
+/// end; axes with a zero period are not repeated. The surface must fit in
+/// one cell around the origin for the distances to stay valid.
+pub struct Repetition {
+    sdf: Arc<dyn Sdf>,
+    period: Vec3,
+}
+
+impl Repetition {
+    pub fn new(sdf: Arc<dyn Sdf>, period: Vec3) -> Self {
+        Repetition { sdf, period }
+    }
+}
+
+impl Sdf for Repetition {
+    fn distance(&self, p: &Point3) -> f64 {
+        let wrap = |x: f64, period: f64| {
+            if period > 0.0 {
+                x - period * (x / period).round()
+            } else {
+                x
+            }
+        };
+        let q = Point3::new(
+            wrap(p.x(), self.period.x()),
+            wrap(p.y(), self.period.y()),
+            wrap(p.z(), self.period.z()),
+        );
+        self.sdf.distance(&q)
+    }
+
+    /// Unbounded along the repeated axes.
+    fn bounding_box(&self) -> Option<Aabb> {
+        if (0..3).any(|axis| self.period[axis] > 0.0) {
+            return None;
+        }
+        self.sdf.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{Repetition, SmoothSubtraction, SmoothUnion};
+    use crate::{
+        sdf::{primitives::SphereSdf, Sdf},
+        vec3::{Point3, Vec3},
+    };
+
+    fn sphere(x: f64) -> Arc<dyn Sdf> {
+        Arc::new(SphereSdf::new(Point3::new(x, 0, 0), 1.0))
+    }
+
+    #[test]
+    fn smooth_union() {
+        let sharp = SmoothUnion::new(sphere(-1.0), sphere(1.0), 0.0);
+        let smooth = SmoothUnion::new(sphere(-1.0), sphere(1.0), 0.5);
+        // Far from the seam both agree with the plain union.
+        for p in [Point3::new(-3, 0, 0), Point3::new(3, 0, 0)] {
+            assert_eq!(smooth.distance(&p), sharp.distance(&p));
+        }
+        // Around the seam the fillet fills the crease.
+        let seam = Point3::new(0, 1, 0);
+        assert!(sharp.distance(&seam) > 0.0);
+        assert!(smooth.distance(&seam) < sharp.distance(&seam));
+        assert!((smooth.distance(&seam) - (2.0_f64.sqrt() - 1.0 - 0.125)).abs() < 1e-12);
+        let bbox = smooth.bounding_box().unwrap();
+        assert_eq!(*bbox.max(), Point3::new(2.125, 1.125, 1.125));
+    }
+
+    #[test]
+    fn smooth_subtraction() {
+        let bite = SmoothSubtraction::new(sphere(0.0), sphere(1.0), 0.0);
+        assert_eq!(bite.distance(&Point3::new(0.5, 0, 0)), 0.5);
+        assert_eq!(bite.distance(&Point3::new(-0.5, 0, 0)), -0.5);
+        let rounded = SmoothSubtraction::new(sphere(0.0), sphere(1.0), 0.5);
+        // Rounding removes material along the rim of the cut.
+        let rim = Point3::new(0.5, 0.75_f64.sqrt(), 0);
+        assert!(bite.distance(&rim).abs() < 1e-12);
+        assert!(rounded.distance(&rim) > 0.0);
+        assert_eq!(rounded.bounding_box(), sphere(0.0).bounding_box());
+    }
+
+    #[test]
+    fn repetition() {
+        let row = Repetition::new(sphere(0.0), Vec3::new(4, 0, 0));
+        for x in [-8.0, 0.0, 4.0, 400.0] {
+            assert!((row.distance(&Point3::new(x, 0, 0)) + 1.0).abs() < 1e-9);
+        }
+        assert!((row.distance(&Point3::new(2, 0, 0)) - 1.0).abs() < 1e-12);
+        assert_eq!(row.distance(&Point3::new(0, 5, 0)), 4.0);
+        assert!(row.bounding_box().is_none());
+        let once = Repetition::new(sphere(0.0), Vec3::new(0, 0, 0));
+        assert_eq!(once.bounding_box(), sphere(0.0).bounding_box());
+    }
+}
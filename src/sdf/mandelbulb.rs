@@ -0,0 +1,122 @@
+use crate::{
+    aabb::Aabb,
+    sdf::Sdf,
+    vec3::{Point3, Vec3},
+};
+
+/// Escape radius of the iteration.
+const BAILOUT: f64 = 2.0;
+
+/// The Mandelbulb fractal centered on the origin, with the usual distance
+/// estimate `0.5 ln(r) r / dr` from the running derivative of the
+/// iteration. More iterations bring out finer detail, at a cost per step.
+pub struct Mandelbulb {
+    power: f64,
+    iterations: usize,
+}
+
+impl Mandelbulb {
+    pub fn new(power: f64, iterations: usize) -> Self {
+        Mandelbulb { power, iterations }
+    }
+
+    /// Radius of a sphere around the bulb: every power from 2 up stays
+    /// within the bailout radius, and power 8 within about 1.2.
+    pub fn radius(&self) -> f64 {
+        if self.power >= 8.0 {
+            1.2
+        } else {
+            BAILOUT
+        }
+    }
+}
+
+impl Default for Mandelbulb {
+    /// The classic power 8 bulb.
+    fn default() -> Self {
+        Self::new(8.0, 10)
+    }
+}
+
+impl Sdf for Mandelbulb {
+    fn distance(&self, p: &Point3) -> f64 {
+        // Far away the estimate overshoots; the bounding sphere does not.
+        let outside = p.length() - self.radius();
+        if outside > 1.0 {
+            return outside;
+        }
+        let mut z = *p;
+        let mut dr = 1.0;
+        let mut r = z.length();
+        for _ in 0..self.iterations {
+            if r > BAILOUT || r == 0.0 {
+                break;
+            }
+            // z ← z^power + p, in spherical coordinates.
+            let theta = (z.z() / r).clamp(-1.0, 1.0).acos() * self.power;
+            let phi = z.y().atan2(z.x()) * self.power;
+            dr = r.powf(self.power - 1.0) * self.power * dr + 1.0;
+            let zr = r.powf(self.power);
+            z = Vec3::new(
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+                theta.cos(),
+            ) * zr
+                + *p;
+            r = z.length();
+        }
+        if r == 0.0 {
+            // The origin never escapes.
+            return 0.0;
+        }
+        return 0.5 * r.ln() * r / dr;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius();
+        Some(Aabb::new(Point3::new(-r, -r, -r), Point3::new(r, r, r)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mandelbulb;
+    use crate::{
+        hittable::{test_rng, HitRecord, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        sdf::{Sdf, SdfObject},
+        vec3::{Point3, Vec3},
+    };
+
+    #[test]
+    fn distance_estimate() {
+        let bulb = Mandelbulb::default();
+        // Far away only the bounding sphere matters.
+        assert_eq!(
+            bulb.distance(&Point3::new(0, 0, 100)),
+            100.0 - bulb.radius()
+        );
+        assert!(bulb.distance(&Point3::new(0.1, 0.1, 0.1)) <= 0.0);
+        assert_eq!(bulb.distance(&Point3::new(0, 0, 0)), 0.0);
+        // Near the bulb the estimate stays below the distance to the
+        // center, and positive outside the bounding sphere.
+        for p in [Point3::new(1.5, 0, 0), Point3::new(0, -1.4, 0.5)] {
+            let d = bulb.distance(&p);
+            assert!(d > 0.0 && d < p.length(), "{} at {}", d, p);
+        }
+    }
+
+    #[test]
+    fn traced_through_the_center() {
+        let bulb = SdfObject::new(Mandelbulb::default(), Lambertian::default());
+        let mut rec = HitRecord::default();
+        let r = Ray::new(Point3::new(0, 0, 5), Vec3::new(0, 0, -1));
+        assert!(bulb.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!(rec.t > 3.8 && rec.t < 4.5, "{}", rec.t);
+        assert!(rec.front_face);
+        assert!(Mandelbulb::default().distance(&rec.p).abs() < 1e-3);
+        let miss = Ray::new(Point3::new(0, 1.5, 5), Vec3::new(0, 0, -1));
+        assert!(!bulb.hit(&miss, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    aabb::Aabb,
+    sdf::Sdf,
+    vec3::{Point3, Vec3},
+};
+
+/// Distance from the origin to an axis-aligned box of `half_size`, centered
+/// on it, shrunk by `radius` to leave room for rounding.
+fn box_distance(p: &Vec3, half_size: &Vec3, radius: f64) -> f64 {
+    let q = Vec3::new(
+        p.x().abs() - half_size.x() + radius,
+        p.y().abs() - half_size.y() + radius,
+        p.z().abs() - half_size.z() + radius,
+    );
+    let outside = q.max(&Vec3::new(0, 0, 0)).length();
+    let inside = q.x().max(q.y()).max(q.z()).min(0.0);
+    return outside + inside - radius;
+}
+
+fn centered_box(center: &Point3, half_size: &Vec3) -> Aabb {
+    Aabb::new(center - half_size, center + half_size)
+}
+
+pub struct SphereSdf {
+    center: Point3,
+    radius: f64,
+}
+
+impl SphereSdf {
+    pub fn new(center: Point3, radius: f64) -> Self {
+        SphereSdf { center, radius }
+    }
+}
+
+impl Sdf for SphereSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        (p - &self.center).length() - self.radius
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius;
+        Some(centered_box(&self.center, &Vec3::new(r, r, r)))
+    }
+}
+
+/// Axis-aligned box extending `half_size` from its center along each axis.
+pub struct BoxSdf {
+    center: Point3,
+    half_size: Vec3,
+}
+
+impl BoxSdf {
+    pub fn new(center: Point3, half_size: Vec3) -> Self {
+        BoxSdf { center, half_size }
+    }
+}
+
+impl Sdf for BoxSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        box_distance(&(p - &self.center), &self.half_size, 0.0)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(centered_box(&self.center, &self.half_size))
+    }
+}
+
+/// Box of the same outer size as a `BoxSdf` whose edges and corners are
+/// rounded with `radius`.
+pub struct RoundBoxSdf {
+    center: Point3,
+    half_size: Vec3,
+    radius: f64,
+}
+
+impl RoundBoxSdf {
+    /// `radius` is at most the smallest half size.
+    pub fn new(center: Point3, half_size: Vec3, radius: f64) -> Self {
+        let smallest = half_size.x().min(half_size.y()).min(half_size.z());
+        RoundBoxSdf {
+            center,
+            half_size,
+            radius: radius.clamp(0.0, smallest),
+        }
+    }
+}
+
+impl Sdf for RoundBoxSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        box_distance(&(p - &self.center), &self.half_size, self.radius)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(centered_box(&self.center, &self.half_size))
+    }
+}
+
+/// Torus around the vertical axis through `center`, like `Torus`.
+pub struct TorusSdf {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+}
+
+impl TorusSdf {
+    pub fn new(center: Point3, major_radius: f64, minor_radius: f64) -> Self {
+        TorusSdf {
+            center,
+            major_radius,
+            minor_radius,
+        }
+    }
+}
+
+impl Sdf for TorusSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        let q = p - &self.center;
+        let ring = (q.x() * q.x() + q.z() * q.z()).sqrt() - self.major_radius;
+        return (ring * ring + q.y() * q.y()).sqrt() - self.minor_radius;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let outer = self.major_radius + self.minor_radius;
+        Some(centered_box(
+            &self.center,
+            &Vec3::new(outer, self.minor_radius, outer),
+        ))
+    }
+}
+
+/// Points within `radius` of the segment from `a` to `b`.
+pub struct CapsuleSdf {
+    a: Point3,
+    b: Point3,
+    radius: f64,
+}
+
+impl CapsuleSdf {
+    pub fn new(a: Point3, b: Point3, radius: f64) -> Self {
+        CapsuleSdf { a, b, radius }
+    }
+}
+
+impl Sdf for CapsuleSdf {
+    fn distance(&self, p: &Point3) -> f64 {
+        let pa = p - &self.a;
+        let ba = self.b - self.a;
+        let length_squared = ba.length_squared();
+        let h = if length_squared > 0.0 {
+            (pa.dot(&ba) / length_squared).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        return (pa - ba * h).length() - self.radius;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.a.min(&self.b) - r, self.a.max(&self.b) + r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoxSdf, CapsuleSdf, RoundBoxSdf, SphereSdf, TorusSdf};
+    use crate::{
+        sdf::Sdf,
+        vec3::{Point3, Vec3},
+    };
+
+    fn assert_distance(sdf: &dyn Sdf, p: Point3, expected: f64) {
+        let d = sdf.distance(&p);
+        assert!(
+            (d - expected).abs() < 1e-12,
+            "{} at {}, not {}",
+            d,
+            p,
+            expected
+        );
+    }
+
+    #[test]
+    fn sphere() {
+        let s = SphereSdf::new(Point3::new(1, 0, 0), 2.0);
+        assert_distance(&s, Point3::new(1, 0, 0), -2.0);
+        assert_distance(&s, Point3::new(1, 5, 0), 3.0);
+        assert_distance(&s, Point3::new(3, 0, 0), 0.0);
+        assert_eq!(*s.bounding_box().unwrap().min(), Point3::new(-1, -2, -2));
+    }
+
+    #[test]
+    fn boxes() {
+        let b = BoxSdf::new(Point3::new(0, 0, 0), Vec3::new(1, 2, 3));
+        assert_distance(&b, Point3::new(0, 0, 0), -1.0);
+        assert_distance(&b, Point3::new(3, 0, 0), 2.0);
+        // Beyond a corner the distance is to the corner itself.
+        assert_distance(&b, Point3::new(4, 6, 3), 5.0);
+        assert_distance(&b, Point3::new(0.5, 2, 0), 0.0);
+
+        let round = RoundBoxSdf::new(Point3::new(0, 0, 0), Vec3::new(1, 1, 1), 0.5);
+        // Faces stay put, corners are pulled in.
+        assert_distance(&round, Point3::new(3, 0, 0), 2.0);
+        let corner = 0.5 + 0.5 / 3.0_f64.sqrt();
+        assert_distance(&round, Point3::new(corner, corner, corner), 0.0);
+        assert!(b.distance(&Point3::new(1, 1, 1)) < round.distance(&Point3::new(1, 1, 1)));
+    }
+
+    #[test]
+    fn torus_and_capsule() {
+        let t = TorusSdf::new(Point3::new(0, 1, 0), 2.0, 0.5);
+        assert_distance(&t, Point3::new(2, 1, 0), -0.5);
+        assert_distance(&t, Point3::new(0, 1, 0), 1.5);
+        assert_distance(&t, Point3::new(0, 3.5, -2), 2.0);
+        let bbox = t.bounding_box().unwrap();
+        assert_eq!(*bbox.max(), Point3::new(2.5, 1.5, 2.5));
+
+        let c = CapsuleSdf::new(Point3::new(0, 0, 0), Point3::new(0, 2, 0), 0.5);
+        assert_distance(&c, Point3::new(1, 1, 0), 0.5);
+        assert_distance(&c, Point3::new(0, 4, 0), 1.5);
+        assert_distance(&c, Point3::new(0, -1, 0), 0.5);
+        let dot = CapsuleSdf::new(Point3::new(0, 0, 0), Point3::new(0, 0, 0), 1.0);
+        assert_distance(&dot, Point3::new(0, 3, 0), 2.0);
+    }
+}
@@ -1,7 +1,14 @@
+use std::f64::consts::PI;
+
+use rand::RngCore;
+
 use crate::{
-    hittable::{HitRecord, Hittable},
+    aabb::Aabb,
+    hittable::{crossing_intervals, HitRecord, Hittable, Interval},
     material::Material,
-    vec3::Point3,
+    onb::Onb,
+    ray::Ray,
+    vec3::{Point3, Vec3},
 };
 
 pub struct Sphere {
@@ -29,48 +36,126 @@ impl Sphere {
     pub fn material(&self) -> Box<dyn Material + 'static> {
         self.material.clone_box()
     }
+
+    /// Cosine of the half-angle of the cone in which the sphere is seen
+    /// from `to_center` away from its center, or `None` from inside it.
+    fn cos_theta_max(&self, to_center: &Vec3) -> Option<f64> {
+        let distance_squared = to_center.length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return None;
+        }
+        Some((1.0 - radius_squared / distance_squared).sqrt())
+    }
 }
 
-impl Hittable for Sphere {
-    fn hit(&self, r: &crate::ray::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        let center = self.center();
-        let radius = self.radius();
-        let oc = r.origin() - center;
-        let a = r.direction().length_squared();
-        let half_b = oc.dot(r.direction());
-        let c = oc.length_squared() - radius * radius;
-        let discriminant = half_b * half_b - a * c;
-        if discriminant > 0.0 {
-            let root = discriminant.sqrt();
-            let temp = (-half_b - root) / a;
-            if temp < t_max && temp > t_min {
-                rec.t = temp;
-                rec.p = r.at(rec.t);
-                rec.normal = (rec.p - *center) / radius;
-                let outward_normal = (rec.p - *center) / radius;
-                rec.set_face_normal(r, &outward_normal);
-                rec.material = self.material();
-                return true;
-            }
-            let temp = (-half_b + root) / a;
+/// Spherical coordinates of the point `p` of the unit sphere: `u` goes
+/// around the Y axis starting from -X, `v` from the south to the north pole.
+pub(crate) fn sphere_uv(p: &Point3) -> (f64, f64) {
+    let theta = (-p.y()).clamp(-1.0, 1.0).acos();
+    let phi = (-p.z()).atan2(p.x()) + PI;
+    (phi / (2.0 * PI), theta / PI)
+}
+
+/// Intersects `r` with the sphere at `center` and fills in everything in
+/// `rec` but the material. A negative radius turns the normals inwards.
+pub(crate) fn hit_sphere(
+    center: &Point3,
+    radius: f64,
+    r: &Ray,
+    t_min: f64,
+    t_max: f64,
+    rec: &mut HitRecord,
+) -> bool {
+    let oc = r.origin() - center;
+    let a = r.direction().length_squared();
+    let half_b = oc.dot(r.direction());
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+    if discriminant > 0.0 {
+        let root = discriminant.sqrt();
+        for temp in [(-half_b - root) / a, (-half_b + root) / a] {
             if temp < t_max && temp > t_min {
                 rec.t = temp;
                 rec.p = r.at(rec.t);
-                rec.normal = (rec.p - *center) / radius;
                 let outward_normal = (rec.p - *center) / radius;
                 rec.set_face_normal(r, &outward_normal);
-                rec.material = self.material();
+                (rec.u, rec.v) = sphere_uv(&(outward_normal * radius.signum()));
                 return true;
             }
         }
+    }
+    return false;
+}
+
+impl Hittable for Sphere {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        if hit_sphere(self.center(), self.radius(), r, t_min, t_max, rec) {
+            rec.material = self.material();
+            return true;
+        }
         return false;
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = self.radius.abs();
+        let half = Vec3::new(r, r, r);
+        Some(Aabb::new(self.center - half, self.center + half))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.radius > 0.0
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+
+    /// Uniform over the cone of directions in which the sphere is seen, or
+    /// over all directions from inside it.
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let to_center = self.center - *origin;
+        match self.cos_theta_max(&to_center) {
+            Some(cos_theta_max) => Onb::new(&to_center)
+                .to_local(direction)
+                .cone_direction_pdf(cos_theta_max),
+            None => Vec3::unit_vector_pdf(),
+        }
+    }
+
+    fn random(&self, origin: &Point3, rng: &mut Box<dyn RngCore>) -> Vec3 {
+        let to_center = self.center - *origin;
+        match self.cos_theta_max(&to_center) {
+            Some(cos_theta_max) => {
+                Onb::new(&to_center).to_world(&Vec3::random_cone_direction(cos_theta_max, rng))
+            }
+            None => Vec3::random_unit_vector(rng),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Sphere;
-    use crate::{material::lambertian::Lambertian, vec3::Point3};
+    use std::f64::consts::PI;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::{sphere_uv, Sphere};
+    use crate::{
+        aabb::Aabb,
+        hittable::{test_rng, HitRecord, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
 
     #[test]
     fn radius() {
@@ -87,4 +172,65 @@ mod tests {
             Point3::new(1, 2, 3),
         )
     }
+
+    #[test]
+    fn bounding_box() {
+        assert_eq!(
+            Sphere::new(Point3::new(1, 2, 3), 2.0, Lambertian::default()).bounding_box(),
+            Some(Aabb::new(Point3::new(-1, 0, 1), Point3::new(3, 4, 5))),
+        )
+    }
+
+    #[test]
+    fn uv_coordinates() {
+        let close = |(u, v): (f64, f64), (eu, ev): (f64, f64)| {
+            assert!(
+                (u - eu).abs() < 1e-12 && (v - ev).abs() < 1e-12,
+                "{} {}",
+                u,
+                v
+            )
+        };
+        close(sphere_uv(&Point3::new(1, 0, 0)), (0.5, 0.5));
+        close(sphere_uv(&Point3::new(0, 1, 0)), (0.5, 1.0));
+        close(sphere_uv(&Point3::new(0, -1, 0)), (0.5, 0.0));
+        close(sphere_uv(&Point3::new(-1, 0, 0)), (0.0, 0.5));
+        close(sphere_uv(&Point3::new(0, 0, 1)), (0.25, 0.5));
+        close(sphere_uv(&Point3::new(0, 0, -1)), (0.75, 0.5));
+    }
+
+    #[test]
+    fn hit_sets_uv() {
+        let sphere = Sphere::new(Point3::new(0, 0, -3), 2.0, Lambertian::default());
+        let mut rec = HitRecord::default();
+        let r = Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1));
+        assert!(sphere.hit(&r, 0.001, f64::INFINITY, &mut rec, &mut test_rng()));
+        assert!((rec.u - 0.25).abs() < 1e-12 && (rec.v - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn samples_the_visible_cone() {
+        let sphere = Sphere::new(Point3::new(0, 0, -2), 1.0, Lambertian::default());
+        let origin = Point3::new(0, 0, 0);
+        let mut rng: Box<dyn RngCore> = Box::new(ChaCha8Rng::seed_from_u64(0));
+        // Seen from 2 away, the sphere spans a cone of half-angle 30°.
+        let expected = 1.0 / (2.0 * PI * (1.0 - 0.75_f64.sqrt()));
+        for _ in 0..1000 {
+            let direction = sphere.random(&origin, &mut rng);
+            assert!(direction.unit().dot(&Vec3::new(0, 0, -1)) >= 0.75_f64.sqrt() - 1e-9);
+            assert!((sphere.pdf_value(&origin, &direction) - expected).abs() < 1e-9);
+        }
+        // The density integrates to one over all directions.
+        let n = 200_000;
+        let total: f64 = (0..n)
+            .map(|_| sphere.pdf_value(&origin, &Vec3::random_unit_vector(&mut rng)))
+            .sum();
+        assert!((total / n as f64 * 4.0 * PI - 1.0).abs() < 0.02);
+        assert_eq!(sphere.pdf_value(&origin, &Vec3::new(0, 0, 1)), 0.0);
+        let inside = Point3::new(0, 0, -2);
+        assert_eq!(
+            sphere.pdf_value(&inside, &Vec3::new(0, 0, 1)),
+            1.0 / (4.0 * PI)
+        );
+    }
 }
@@ -0,0 +1,21 @@
+pub mod checker;
+pub mod image_texture;
+pub mod noise;
+pub mod perlin;
+pub mod solid_color;
+
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3};
+
+/// Spatially varying material parameter, evaluated at the surface
+/// coordinates `(u, v)` and the point `p` of a hit.
+pub trait Texture: Sync + Send {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+impl Texture for Arc<dyn Texture> {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        self.as_ref().value(u, v, p)
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3};
+
+use super::Texture;
+
+/// Solid 3D checkerboard of cubes of side `scale`, alternating between two
+/// textures. Being defined in space rather than in `(u, v)`, it needs no
+/// texture coordinates.
+#[derive(Clone)]
+pub struct Checker {
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+    scale: f64,
+}
+
+impl Checker {
+    pub fn new(even: Arc<dyn Texture>, odd: Arc<dyn Texture>, scale: f64) -> Self {
+        Self { even, odd, scale }
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = |x: f64| (x / self.scale).floor() as i64;
+        if (cell(p.x()) + cell(p.y()) + cell(p.z())).rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::Checker;
+    use crate::{
+        texture::{solid_color::SolidColor, Texture},
+        vec3::{Color, Point3},
+    };
+
+    #[test]
+    fn alternates_in_every_axis() {
+        let white = Color::new(1, 1, 1);
+        let black = Color::default();
+        let checker = Checker::new(
+            Arc::new(SolidColor::new(white)),
+            Arc::new(SolidColor::new(black)),
+            0.5,
+        );
+        let at = |x: f64, y: f64, z: f64| checker.value(0.0, 0.0, &Point3::new(x, y, z));
+        assert_eq!(at(0.1, 0.1, 0.1), white);
+        assert_eq!(at(0.6, 0.1, 0.1), black);
+        assert_eq!(at(0.1, 0.6, 0.1), black);
+        assert_eq!(at(0.1, 0.1, 0.6), black);
+        assert_eq!(at(0.6, 0.6, 0.1), white);
+        assert_eq!(at(-0.1, 0.1, 0.1), black);
+        assert_eq!(at(-0.6, 0.1, 0.1), white);
+    }
+}
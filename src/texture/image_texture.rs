@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use image::{DynamicImage, Rgb32FImage};
+
+use crate::{
+    tonemap::srgb_decode,
+    vec3::{Color, Point3},
+};
+
+use super::Texture;
+
+/// Image mapped onto `(u, v)` in [0, 1]², with `v = 0` at the bottom row.
+/// Coordinates outside that range wrap around.
+#[derive(Clone)]
+pub struct ImageTexture {
+    image: Rgb32FImage,
+}
+
+impl ImageTexture {
+    /// Loads an image file. 8 and 16-bit images are taken to be sRGB encoded
+    /// and are converted to linear values; float images are used as is.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, image::ImageError> {
+        Ok(Self::from_image(image::open(path)?))
+    }
+
+    pub fn from_image(image: DynamicImage) -> Self {
+        let is_float = matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        let mut image = image.into_rgb32f();
+        if !is_float {
+            for channel in image.iter_mut() {
+                *channel = srgb_decode(*channel as f64) as f32;
+            }
+        }
+        Self { image }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.image.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _: &Point3) -> Color {
+        let (width, height) = self.image.dimensions();
+        if width == 0 || height == 0 {
+            return Color::default();
+        }
+        let wrap = |x: f64| {
+            if (0.0..=1.0).contains(&x) {
+                x
+            } else {
+                x.rem_euclid(1.0)
+            }
+        };
+        let u = wrap(u);
+        let v = 1.0 - wrap(v);
+        let x = ((u * width as f64) as u32).min(width - 1);
+        let y = ((v * height as f64) as u32).min(height - 1);
+        let [r, g, b] = self.image.get_pixel(x, y).0;
+        Color::new(r, g, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, Rgb, Rgb32FImage, RgbImage};
+
+    use super::ImageTexture;
+    use crate::{
+        texture::Texture,
+        vec3::{Color, Point3},
+    };
+
+    fn value(texture: &ImageTexture, u: f64, v: f64) -> Color {
+        texture.value(u, v, &Point3::default())
+    }
+
+    #[test]
+    fn v_points_up() {
+        // Red on the top row, blue on the bottom one.
+        let image = RgbImage::from_fn(2, 2, |_, y| {
+            if y == 0 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        let texture = ImageTexture::from_image(DynamicImage::ImageRgb8(image));
+        assert_eq!((texture.width(), texture.height()), (2, 2));
+        assert_eq!(value(&texture, 0.25, 0.9), Color::new(1, 0, 0));
+        assert_eq!(value(&texture, 0.25, 0.1), Color::new(0, 0, 1));
+        assert_eq!(value(&texture, 1.0, 1.0), Color::new(1, 0, 0));
+        // Wraps around.
+        assert_eq!(value(&texture, 1.25, -0.1), Color::new(1, 0, 0));
+    }
+
+    #[test]
+    fn eight_bit_images_are_linearized() {
+        let image = RgbImage::from_pixel(1, 1, Rgb([188, 188, 188]));
+        let texture = ImageTexture::from_image(DynamicImage::ImageRgb8(image));
+        assert!((value(&texture, 0.5, 0.5).x() - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn float_images_are_linear() {
+        let image = Rgb32FImage::from_pixel(1, 1, Rgb([4.0, 0.5, 0.0]));
+        let texture = ImageTexture::from_image(DynamicImage::ImageRgb32F(image));
+        assert_eq!(value(&texture, 0.5, 0.5), Color::new(4.0, 0.5, 0.0));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::vec3::{Color, Point3};
+
+use super::{perlin::Perlin, Texture};
+
+/// Number of noise octaves summed by the patterns.
+const OCTAVES: usize = 7;
+
+/// How a [`NoiseTexture`] turns Perlin noise into a blend factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoisePattern {
+    /// Sine bands along Z, distorted by turbulence.
+    Marble,
+    /// Concentric rings around the Y axis, slightly warped.
+    Wood,
+    /// Soft fBm blobs.
+    Clouds,
+}
+
+/// Procedural texture blending between two colors with a noise pattern.
+/// `scale` is the frequency of the pattern: larger values give finer
+/// detail.
+#[derive(Clone)]
+pub struct NoiseTexture {
+    perlin: Perlin,
+    pattern: NoisePattern,
+    scale: f64,
+    low: Color,
+    high: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(pattern: NoisePattern, seed: u64, scale: f64, low: Color, high: Color) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            pattern,
+            scale,
+            low,
+            high,
+        }
+    }
+
+    /// White marble with dark gray veins.
+    pub fn marble(seed: u64, scale: f64) -> Self {
+        Self::new(
+            NoisePattern::Marble,
+            seed,
+            scale,
+            Color::new(0.1, 0.1, 0.12),
+            Color::new(0.9, 0.9, 0.88),
+        )
+    }
+
+    /// Light and dark brown growth rings.
+    pub fn wood(seed: u64, scale: f64) -> Self {
+        Self::new(
+            NoisePattern::Wood,
+            seed,
+            scale,
+            Color::new(0.35, 0.18, 0.07),
+            Color::new(0.75, 0.52, 0.3),
+        )
+    }
+
+    /// White clouds on a blue sky.
+    pub fn clouds(seed: u64, scale: f64) -> Self {
+        Self::new(
+            NoisePattern::Clouds,
+            seed,
+            scale,
+            Color::new(0.3, 0.5, 0.9),
+            Color::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    pub fn pattern(&self) -> NoisePattern {
+        self.pattern
+    }
+
+    pub fn low(&self) -> Color {
+        self.low
+    }
+
+    pub fn high(&self) -> Color {
+        self.high
+    }
+
+    /// Blend factor between the low and the high color at `p`, in [0, 1].
+    pub fn factor(&self, p: &Point3) -> f64 {
+        let p = p * self.scale;
+        return match self.pattern {
+            NoisePattern::Marble => {
+                0.5 * (1.0 + (p.z() + 10.0 * self.perlin.turbulence(&p, OCTAVES)).sin())
+            }
+            NoisePattern::Wood => {
+                let r = (p.x() * p.x() + p.z() * p.z()).sqrt();
+                let rings = 4.0 * r + 2.0 * self.perlin.noise(&p);
+                let ring = rings - rings.floor();
+                // Sharp dark edge at the start of every ring.
+                ring.powf(0.6)
+            }
+            NoisePattern::Clouds => {
+                (0.5 + 0.8 * self.perlin.fbm(&(p * 2.0), OCTAVES)).clamp(0.0, 1.0)
+            }
+        };
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _: f64, _: f64, p: &Point3) -> Color {
+        let t = self.factor(p);
+        self.low * (1.0 - t) + self.high * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NoisePattern, NoiseTexture};
+    use crate::{
+        texture::Texture,
+        vec3::{Color, Point3},
+    };
+
+    fn samples() -> impl Iterator<Item = Point3> {
+        (0..300).map(|i| {
+            let i = i as f64;
+            Point3::new(i * 0.0731, i * 0.0217 - 3.0, i * -0.0433)
+        })
+    }
+
+    #[test]
+    fn presets_stay_between_their_colors() {
+        for texture in [
+            NoiseTexture::marble(1, 4.0),
+            NoiseTexture::wood(1, 2.0),
+            NoiseTexture::clouds(1, 1.0),
+        ] {
+            let factors: Vec<f64> = samples().map(|p| texture.factor(&p)).collect();
+            assert!(
+                factors.iter().all(|t| (0.0..=1.0).contains(t)),
+                "{:?}",
+                texture.pattern()
+            );
+            let min = factors.iter().cloned().fold(1.0, f64::min);
+            let max = factors.iter().cloned().fold(0.0, f64::max);
+            assert!(max - min > 0.3, "{:?} is flat", texture.pattern());
+        }
+    }
+
+    #[test]
+    fn blends_the_two_colors() {
+        let low = Color::new(1, 0, 0);
+        let high = Color::new(0, 0, 1);
+        let texture = NoiseTexture::new(NoisePattern::Clouds, 5, 1.0, low, high);
+        for p in samples() {
+            let t = texture.factor(&p);
+            assert_eq!(texture.value(0.0, 0.0, &p), low * (1.0 - t) + high * t);
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let p = Point3::new(0.3, 0.7, -1.2);
+        assert_eq!(
+            NoiseTexture::marble(9, 3.0).value(0.0, 0.0, &p),
+            NoiseTexture::marble(9, 3.0).value(0.0, 0.0, &p)
+        );
+        assert!(samples().any(|p| {
+            NoiseTexture::wood(1, 2.0).factor(&p) != NoiseTexture::wood(2, 2.0).factor(&p)
+        }));
+    }
+}
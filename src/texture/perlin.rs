@@ -0,0 +1,159 @@
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::vec3::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise on the integer lattice. Random unit gradients sit at the
+/// lattice points and are blended with Hermite-smoothed trilinear
+/// interpolation; the noise is zero at every lattice point.
+#[derive(Clone)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Noise generator whose lattice is entirely determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3::random_unit_vector(&mut rng))
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        let perm_x = permutation();
+        let perm_y = permutation();
+        let perm_z = permutation();
+        Self {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    fn gradient(&self, i: i64, j: i64, k: i64) -> &Vec3 {
+        let wrap = |n: i64| (n & (POINT_COUNT as i64 - 1)) as usize;
+        &self.gradients[self.perm_x[wrap(i)] ^ self.perm_y[wrap(j)] ^ self.perm_z[wrap(k)]]
+    }
+
+    /// Noise value at `p`, in [-1, 1].
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let (i, j, k) = (p.x().floor(), p.y().floor(), p.z().floor());
+        let (u, v, w) = (p.x() - i, p.y() - j, p.z() - k);
+        let (i, j, k) = (i as i64, j as i64, k as i64);
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (uu, vv, ww) = (smooth(u), smooth(v), smooth(w));
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * self.gradient(i + di, j + dj, k + dk).dot(&weight);
+                }
+            }
+        }
+        // The dot products reach at most sqrt(3)/2 in magnitude.
+        return (accum * 2.0 / 3.0_f64.sqrt()).clamp(-1.0, 1.0);
+    }
+
+    /// Sum of `octaves` layers of noise, each at twice the frequency and
+    /// half the amplitude of the previous one.
+    pub fn fbm(&self, p: &Point3, octaves: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accum += weight * self.noise(&p);
+            weight *= 0.5;
+            p *= 2;
+        }
+        return accum;
+    }
+
+    /// Like [`Perlin::fbm`] but summing the absolute value of every octave,
+    /// which gives the creases of turbulent patterns.
+    pub fn turbulence(&self, p: &Point3, octaves: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accum += weight * self.noise(&p).abs();
+            weight *= 0.5;
+            p *= 2;
+        }
+        return accum;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Perlin;
+    use crate::vec3::Point3;
+
+    fn samples() -> impl Iterator<Item = Point3> {
+        (0..500).map(|i| {
+            let i = i as f64;
+            Point3::new(i * 0.173, i * 0.311 - 20.0, i * -0.057)
+        })
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let (a, b, c) = (Perlin::new(7), Perlin::new(7), Perlin::new(8));
+        let p = Point3::new(1.3, -2.7, 0.4);
+        assert_eq!(a.noise(&p), b.noise(&p));
+        assert!(samples().any(|p| a.noise(&p) != c.noise(&p)));
+    }
+
+    #[test]
+    fn zero_on_the_lattice() {
+        let perlin = Perlin::new(0);
+        for p in [Point3::new(0, 0, 0), Point3::new(3, -5, 12)] {
+            assert_eq!(perlin.noise(&p), 0.0);
+        }
+    }
+
+    #[test]
+    fn bounded_and_varied() {
+        let perlin = Perlin::new(1);
+        let values: Vec<f64> = samples().map(|p| perlin.noise(&p)).collect();
+        assert!(values.iter().all(|n| (-1.0..=1.0).contains(n)));
+        assert!(values.iter().any(|n| *n > 0.2));
+        assert!(values.iter().any(|n| *n < -0.2));
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert!(mean.abs() < 0.1, "{}", mean);
+    }
+
+    #[test]
+    fn continuous() {
+        let perlin = Perlin::new(2);
+        for p in samples() {
+            let q = p + Point3::new(1e-6, -1e-6, 1e-6);
+            assert!((perlin.noise(&p) - perlin.noise(&q)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn octaves() {
+        let perlin = Perlin::new(3);
+        for p in samples() {
+            assert_eq!(perlin.fbm(&p, 1), perlin.noise(&p));
+            let turbulence = perlin.turbulence(&p, 6);
+            assert!((0.0..2.0).contains(&turbulence));
+            assert!(perlin.fbm(&p, 6).abs() <= turbulence + 1e-12);
+        }
+    }
+}
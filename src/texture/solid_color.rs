@@ -0,0 +1,27 @@
+use crate::vec3::{Color, Point3};
+
+use super::Texture;
+
+/// The same color everywhere.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl From<Color> for SolidColor {
+    fn from(color: Color) -> Self {
+        Self::new(color)
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _: f64, _: f64, _: &Point3) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,176 @@
+use clap::ValueEnum;
+
+use crate::vec3::Color;
+
+/// Curve compressing linear radiance into the displayable [0, 1] range.
+/// Every operator works on each channel on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Operator {
+    /// Clip everything brighter than 1.
+    #[default]
+    Clamp,
+    /// `x / (1 + x)`; never reaches white.
+    Reinhard,
+    /// Reinhard scaled so that the white point maps to 1.
+    ReinhardExtended,
+    /// Narkowicz's fit of the ACES filmic curve.
+    Aces,
+    /// John Hable's Uncharted 2 filmic curve.
+    Hable,
+}
+
+/// Turns film radiance into display values: exposure, then the tone curve,
+/// then the sRGB transfer function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMap {
+    pub operator: Operator,
+    /// Exposure adjustment in stops; every stop doubles the radiance.
+    pub exposure: f64,
+    /// Linear white point of the extended Reinhard and Hable curves.
+    pub white: f64,
+}
+
+impl Default for ToneMap {
+    fn default() -> Self {
+        ToneMap {
+            operator: Operator::default(),
+            exposure: 0.0,
+            white: 11.2,
+        }
+    }
+}
+
+fn hable_partial(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+impl ToneMap {
+    /// Tone-mapped linear value of one channel, in [0, 1].
+    pub fn map_channel(&self, x: f64) -> f64 {
+        let x = x.max(0.0) * self.exposure.exp2();
+        let mapped = match self.operator {
+            Operator::Clamp => x,
+            Operator::Reinhard => x / (1.0 + x),
+            Operator::ReinhardExtended => x * (1.0 + x / (self.white * self.white)) / (1.0 + x),
+            Operator::Aces => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                (x * (a * x + b)) / (x * (c * x + d) + e)
+            }
+            Operator::Hable => {
+                // Hable's curve is tuned for an input scaled by two.
+                hable_partial(2.0 * x) / hable_partial(2.0 * self.white)
+            }
+        };
+        return mapped.clamp(0.0, 1.0);
+    }
+
+    /// Display-ready sRGB-encoded color, every channel in [0, 1].
+    pub fn apply(&self, c: &Color) -> Color {
+        let encode = |x: f64| srgb_encode(self.map_channel(x));
+        Color::new(encode(c.x()), encode(c.y()), encode(c.z()))
+    }
+}
+
+/// sRGB transfer function: linear segment near black, 2.4 power curve above.
+pub fn srgb_encode(linear: f64) -> f64 {
+    if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Inverse of [`srgb_encode`].
+pub fn srgb_decode(encoded: f64) -> f64 {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{srgb_decode, srgb_encode, Operator, ToneMap};
+    use crate::vec3::Color;
+
+    fn tone_map(operator: Operator) -> ToneMap {
+        ToneMap {
+            operator,
+            ..ToneMap::default()
+        }
+    }
+
+    const OPERATORS: [Operator; 5] = [
+        Operator::Clamp,
+        Operator::Reinhard,
+        Operator::ReinhardExtended,
+        Operator::Aces,
+        Operator::Hable,
+    ];
+
+    #[test]
+    fn srgb_transfer() {
+        assert_eq!(srgb_encode(0.0), 0.0);
+        assert!((srgb_encode(1.0) - 1.0).abs() < 1e-12);
+        assert!((srgb_encode(0.18) - 0.4614).abs() < 1e-3);
+        assert!((srgb_encode(0.001) - 0.01292).abs() < 1e-12);
+        // Both pieces meet at the threshold.
+        let t = 0.0031308;
+        assert!((12.92 * t - (1.055 * f64::powf(t, 1.0 / 2.4) - 0.055)).abs() < 1e-6);
+        for x in [0.0, 0.002, 0.05, 0.5, 1.0] {
+            assert!((srgb_decode(srgb_encode(x)) - x).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn operators_are_monotonic_and_bounded() {
+        for operator in OPERATORS {
+            let t = tone_map(operator);
+            assert_eq!(t.map_channel(0.0), 0.0, "{:?}", operator);
+            assert_eq!(t.map_channel(-1.0), 0.0, "{:?}", operator);
+            let mut previous = 0.0;
+            for i in 1..200 {
+                let y = t.map_channel(i as f64 * 0.1);
+                assert!(y >= previous && y <= 1.0, "{:?} at {}", operator, i);
+                previous = y;
+            }
+        }
+    }
+
+    #[test]
+    fn operator_values() {
+        assert_eq!(tone_map(Operator::Clamp).map_channel(4.0), 1.0);
+        assert_eq!(tone_map(Operator::Reinhard).map_channel(1.0), 0.5);
+        assert!(tone_map(Operator::Reinhard).map_channel(1e6) < 1.0);
+        let extended = tone_map(Operator::ReinhardExtended);
+        assert!((extended.map_channel(extended.white) - 1.0).abs() < 1e-12);
+        assert!((tone_map(Operator::Aces).map_channel(1.0) - 0.8038).abs() < 1e-3);
+        let hable = tone_map(Operator::Hable);
+        assert!((hable.map_channel(hable.white) - 1.0).abs() < 1e-12);
+        assert!(hable.map_channel(hable.white / 2.0) < 1.0);
+    }
+
+    #[test]
+    fn exposure_is_in_stops() {
+        let brighter = ToneMap {
+            exposure: 1.0,
+            ..ToneMap::default()
+        };
+        let darker = ToneMap {
+            exposure: -2.0,
+            ..ToneMap::default()
+        };
+        assert_eq!(brighter.map_channel(0.25), 0.5);
+        assert_eq!(darker.map_channel(0.8), 0.2);
+    }
+
+    #[test]
+    fn apply_encodes_every_channel() {
+        let c = ToneMap::default().apply(&Color::new(0.0, 0.18, 2.0));
+        assert_eq!(c.x(), 0.0);
+        assert!((c.y() - 0.4614).abs() < 1e-3);
+        assert!((c.z() - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,236 @@
+use std::f64::consts::PI;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    cylinder::axial_u,
+    hittable::{crossing_intervals, HitRecord, Hittable, Interval},
+    material::Material,
+    poly::solve_quartic,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Torus around the Y axis, centered on the origin: a tube of
+/// `minor_radius` swept along the circle of `major_radius` in the XZ plane.
+/// Place it with a `Transform`. `u` goes around the Y axis and `v` around
+/// the tube, starting from its inner side.
+pub struct Torus {
+    major_radius: f64,
+    minor_radius: f64,
+    material: Box<dyn Material + 'static>,
+}
+
+impl Torus {
+    pub fn new<M: Material + 'static>(major_radius: f64, minor_radius: f64, material: M) -> Self {
+        Torus {
+            major_radius,
+            minor_radius,
+            material: Box::new(material),
+        }
+    }
+
+    pub fn major_radius(&self) -> f64 {
+        self.major_radius
+    }
+
+    pub fn minor_radius(&self) -> f64 {
+        self.minor_radius
+    }
+
+    /// Ray parameters of every crossing of the surface, in increasing order.
+    fn roots(&self, r: &Ray) -> Vec<f64> {
+        let length = r.direction().length();
+        let d = r.direction() / length;
+        // The quartic loses precision when the origin is far away compared
+        // to the torus, so start from the closest approach to the bounding
+        // sphere instead, and solve in distances along the unit direction.
+        let bound = self.major_radius + self.minor_radius;
+        let closest = -r.origin().dot(&d);
+        let miss = r.origin().length_squared() - closest * closest;
+        if miss > bound * bound {
+            return vec![];
+        }
+        let skip = (closest - bound).max(0.0);
+        let o = r.origin() + &(d * skip);
+
+        // (|p|² + R² - r²)² = 4 R² (x² + z²) along p = o + s d, with |d| = 1.
+        let big = self.major_radius * self.major_radius;
+        let small = self.minor_radius * self.minor_radius;
+        let h = 2.0 * o.dot(&d);
+        let i = o.length_squared() + big - small;
+        let j = 4.0 * big;
+        let roots = solve_quartic(
+            1.0,
+            2.0 * h,
+            h * h + 2.0 * i - j * (d.x() * d.x() + d.z() * d.z()),
+            2.0 * h * i - 2.0 * j * (o.x() * d.x() + o.z() * d.z()),
+            i * i - j * (o.x() * o.x() + o.z() * o.z()),
+        );
+        return roots.into_iter().map(|s| (s + skip) / length).collect();
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let t = match self.roots(r).into_iter().find(|t| *t > t_min && *t < t_max) {
+            Some(t) => t,
+            None => return false,
+        };
+        let p = r.at(t);
+        // The normal points from the closest point of the center circle.
+        let ring = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let center = if ring > 0.0 {
+            Vec3::new(p.x(), 0, p.z()) * (self.major_radius / ring)
+        } else {
+            Vec3::new(self.major_radius, 0, 0)
+        };
+        let normal = (p - center).unit();
+        rec.t = t;
+        rec.p = p;
+        rec.set_face_normal(r, &normal);
+        rec.u = axial_u(&p);
+        rec.v = (p.y().atan2(ring - self.major_radius) + PI) / (2.0 * PI);
+        rec.material = self.material.clone_box();
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (outer, r) = (self.major_radius + self.minor_radius, self.minor_radius);
+        Some(Aabb::new(
+            Point3::new(-outer, -r, -outer),
+            Point3::new(outer, r, outer),
+        ))
+    }
+
+    fn is_closed(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        crossing_intervals(self, r, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Torus;
+    use crate::{
+        hittable::hit,
+        mat4::Mat4,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        transform::Transform,
+        vec3::{Point3, Vec3},
+    };
+
+    fn torus() -> Torus {
+        Torus::new(2.0, 0.5, Lambertian::default())
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    /// Distance of `p` to the surface of `torus()`.
+    fn surface_distance(p: &Point3) -> f64 {
+        let ring = (p.x() * p.x() + p.z() * p.z()).sqrt() - 2.0;
+        return (ring * ring + p.y() * p.y()).sqrt() - 0.5;
+    }
+
+    #[test]
+    fn crossings_through_the_hole() {
+        let torus = torus();
+        let r = Ray::new(Point3::new(-10, 0, 0), Vec3::new(1, 0, 0));
+        let roots = torus.roots(&r);
+        assert_eq!(roots.len(), 4, "{:?}", roots);
+        for (root, expected) in roots.iter().zip([7.5, 8.5, 11.5, 12.5]) {
+            assert!((root - expected).abs() < 1e-9, "{:?}", roots);
+        }
+        let rec = hit(&torus, &r).unwrap();
+        assert_close(rec.normal, Vec3::new(-1, 0, 0));
+        assert!(rec.front_face);
+        assert!((rec.v - 0.5).abs() < 1e-9);
+
+        // From inside the hole the next hit is the inner side of the tube.
+        let rec = hit(&torus, &Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, 1))).unwrap();
+        assert!((rec.t - 1.5).abs() < 1e-9);
+        assert_close(rec.normal, Vec3::new(0, 0, -1));
+        assert!(rec.v.abs() < 1e-9 || (rec.v - 1.0).abs() < 1e-9);
+        // Straight down the axis there is nothing.
+        assert!(hit(&torus, &Ray::new(Point3::new(0, 5, 0), Vec3::new(0, -1, 0))).is_none());
+    }
+
+    #[test]
+    fn tangent_and_grazing_rays() {
+        let torus = torus();
+        // Touching the top of the tube on both sides of the hole.
+        let r = Ray::new(Point3::new(-10, 0.5, 0), Vec3::new(1, 0, 0));
+        let rec = hit(&torus, &r).unwrap();
+        assert!((rec.t - 8.0).abs() < 1e-6, "{}", rec.t);
+        // Perpendicular to the ray, so its side is arbitrary.
+        assert!((rec.normal.y().abs() - 1.0).abs() < 1e-6);
+
+        // Just below, two close crossings on each side, all on the surface.
+        let r = Ray::new(Point3::new(-10, 0.5 - 1e-6, 0), Vec3::new(1, 0, 0));
+        let roots = torus.roots(&r);
+        assert_eq!(roots.len(), 4, "{:?}", roots);
+        for t in roots {
+            assert!(surface_distance(&r.at(t)).abs() < 1e-9);
+        }
+        assert!(hit(
+            &torus,
+            &Ray::new(Point3::new(-10, 0.5 + 1e-6, 0), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+
+        // Tangent to the outer equator, and just outside it.
+        let rec = hit(
+            &torus,
+            &Ray::new(Point3::new(-10, 0, 2.5), Vec3::new(1, 0, 0)),
+        )
+        .unwrap();
+        assert!((rec.t - 10.0).abs() < 1e-4, "{}", rec.t);
+        assert!(hit(
+            &torus,
+            &Ray::new(Point3::new(-10, 0, 2.5 + 1e-6), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+        // Tangent to the inner equator from inside the hole grazes the
+        // tube and goes on to cross the far side.
+        let r = Ray::new(Point3::new(-10, 0, 1.5), Vec3::new(1, 0, 0));
+        let rec = hit(&torus, &r).unwrap();
+        assert!(surface_distance(&rec.p).abs() < 1e-9);
+        assert!(rec.p.x() < 0.0);
+    }
+
+    #[test]
+    fn far_away_rays_stay_accurate() {
+        let torus = torus();
+        let r = Ray::new(Point3::new(-1e6, 0.2, 0.3), Vec3::new(1, 0, 0));
+        let rec = hit(&torus, &r).unwrap();
+        assert!(surface_distance(&rec.p).abs() < 1e-9);
+        assert!(rec.p.x() < -2.0);
+    }
+
+    #[test]
+    fn placed_with_transform() {
+        // Standing up in the XY plane and moved back; rays are not unit
+        // length in the space of the torus.
+        let torus = Transform::new(torus(), Mat4::scaling(&Vec3::new(2, 2, 2)))
+            .rotate(&Vec3::new(1, 0, 0), 90.0)
+            .translate(&Vec3::new(0, 0, -10));
+        let rec = hit(&torus, &Ray::new(Point3::new(4, 0, 0), Vec3::new(0, 0, -1))).unwrap();
+        assert!((rec.t - 9.0).abs() < 1e-9);
+        assert_close(rec.normal, Vec3::new(0, 0, 1));
+        assert!(hit(&torus, &Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1))).is_none());
+    }
+}
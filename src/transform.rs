@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable, Interval},
+    mat4::Mat4,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Places an object with an affine transform. Rays are brought into the
+/// space of the object, and hits back into world space. The object is
+/// shared, so one mesh can be instanced at many poses.
+pub struct Transform {
+    object: Arc<dyn Hittable>,
+    matrix: Mat4,
+    inverse: Mat4,
+    /// Inverse transpose of `matrix`, which keeps normals perpendicular to
+    /// the surface under non-uniform scaling.
+    normal_matrix: Mat4,
+}
+
+impl Transform {
+    /// Panics if `matrix` cannot be inverted.
+    pub fn new<H: Hittable + 'static>(object: H, matrix: Mat4) -> Self {
+        Self::shared(Arc::new(object), matrix)
+    }
+
+    /// Transformed instance of an object that may have other instances.
+    pub fn shared(object: Arc<dyn Hittable>, matrix: Mat4) -> Self {
+        let inverse = matrix.inverse().expect("transform matrix is singular");
+        Transform {
+            object,
+            matrix,
+            normal_matrix: inverse.transpose(),
+            inverse,
+        }
+    }
+
+    /// Applies `m` after the current transform.
+    pub fn then(self, m: &Mat4) -> Self {
+        let matrix = m * &self.matrix;
+        Self::shared(self.object, matrix)
+    }
+
+    pub fn translate(self, offset: &Vec3) -> Self {
+        self.then(&Mat4::translation(offset))
+    }
+
+    /// Rotates counter-clockwise by `degrees` around `axis`, through the
+    /// origin.
+    pub fn rotate(self, axis: &Vec3, degrees: f64) -> Self {
+        self.then(&Mat4::rotation(axis, degrees))
+    }
+
+    /// Scales each axis by the matching component of `factors`, around the
+    /// origin.
+    pub fn scale(self, factors: &Vec3) -> Self {
+        self.then(&Mat4::scaling(factors))
+    }
+
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+
+    pub fn inverse(&self) -> &Mat4 {
+        &self.inverse
+    }
+}
+
+impl Transform {
+    /// `r` in the space of the object. The direction is not renormalized,
+    /// so `t` means the same in both spaces.
+    fn to_local(&self, r: &Ray) -> Ray {
+        Ray::with_time(
+            self.inverse.transform_point(r.origin()),
+            self.inverse.transform_vector(r.direction()),
+            r.time(),
+        )
+    }
+
+    /// Brings a hit on the object back into world space.
+    fn to_world(&self, rec: &mut HitRecord) {
+        rec.p = self.matrix.transform_point(&rec.p);
+        rec.normal = self.normal_matrix.transform_vector(&rec.normal).unit();
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        if !self.object.hit(&self.to_local(r), t_min, t_max, rec, rng) {
+            return false;
+        }
+        self.to_world(rec);
+        return true;
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox = self.object.bounding_box()?;
+        let (lo, hi) = (bbox.min(), bbox.max());
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = -min;
+        for corner in 0..8 {
+            let pick = |axis: usize| {
+                if corner & (1 << axis) == 0 {
+                    lo[axis]
+                } else {
+                    hi[axis]
+                }
+            };
+            let p = self
+                .matrix
+                .transform_point(&Point3::new(pick(0), pick(1), pick(2)));
+            min = min.min(&p);
+            max = max.max(&p);
+        }
+        Some(Aabb::new(min, max))
+    }
+
+    fn is_closed(&self) -> bool {
+        self.object.is_closed()
+    }
+
+    fn intervals(&self, r: &Ray, rng: &mut Box<dyn RngCore>) -> Vec<Interval> {
+        let mut intervals = self.object.intervals(&self.to_local(r), rng);
+        for interval in intervals.iter_mut() {
+            self.to_world(&mut interval.enter);
+            self.to_world(&mut interval.exit);
+        }
+        return intervals;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::Transform;
+    use crate::{
+        bvh::BvhNode,
+        hittable::{hit, Hittable, HittableList},
+        mat4::Mat4,
+        material::lambertian::Lambertian,
+        ray::Ray,
+        sphere::Sphere,
+        triangle::Triangle,
+        vec3::{Point3, Vec3},
+    };
+
+    fn unit_sphere() -> Sphere {
+        Sphere::new(Point3::new(0, 0, 0), 1.0, Lambertian::default())
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).length() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn translated_sphere() {
+        let sphere =
+            Transform::new(unit_sphere(), Mat4::identity()).translate(&Vec3::new(0, 0, -5));
+        let rec = hit(
+            &sphere,
+            &Ray::new(Point3::new(0, 0, 0), Vec3::new(0, 0, -1)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 4.0);
+        assert_close(rec.p, Point3::new(0, 0, -4));
+        assert_close(rec.normal, Vec3::new(0, 0, 1));
+        assert!(hit(
+            &sphere,
+            &Ray::new(Point3::new(0, 1.5, 0), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn non_uniform_scale_normals() {
+        // Ellipsoid twice as wide along X: the normal follows the gradient
+        // of x²/4 + y² + z², not the direction from the center.
+        let ellipsoid = Transform::new(unit_sphere(), Mat4::scaling(&Vec3::new(2, 1, 1)));
+        let z = 0.5_f64.sqrt();
+        let r = Ray::new(Point3::new(1, 0.5, 5), Vec3::new(0, 0, -1));
+        let rec = hit(&ellipsoid, &r).unwrap();
+        assert_close(rec.p, Point3::new(1, 0.5, z));
+        assert_close(rec.normal, Vec3::new(0.25, 0.5, z).unit());
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn rotated_triangle() {
+        let triangle = Triangle::new(
+            Point3::new(0, 0, 0),
+            Point3::new(1, 0, 0),
+            Point3::new(0, 1, 0),
+            Lambertian::default(),
+        );
+        // Turn the triangle to face +X.
+        let rotated = Transform::new(triangle, Mat4::identity()).rotate(&Vec3::new(0, 1, 0), 90.0);
+        let r = Ray::new(Point3::new(3, 0.25, -0.25), Vec3::new(-1, 0, 0));
+        let rec = hit(&rotated, &r).unwrap();
+        assert!((rec.t - 3.0).abs() < 1e-9);
+        assert_close(rec.normal, Vec3::new(1, 0, 0));
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn bounding_box_of_rotation() {
+        let sphere = Transform::new(unit_sphere(), Mat4::identity())
+            .scale(&Vec3::new(2, 1, 1))
+            .rotate(&Vec3::new(0, 0, 1), 45.0);
+        let bbox = sphere.bounding_box().unwrap();
+        let d = 3.0 / 2.0_f64.sqrt();
+        assert_close(*bbox.min(), Point3::new(-d, -d, -1));
+        assert_close(*bbox.max(), Point3::new(d, d, 1));
+    }
+
+    #[test]
+    fn instances_share_the_object() {
+        let sphere: Arc<dyn Hittable> = Arc::new(unit_sphere());
+        let mut list = HittableList::new();
+        for x in [-3, 0, 3] {
+            list.add(Transform::shared(
+                sphere.clone(),
+                Mat4::translation(&Vec3::new(x, 0, -5)),
+            ));
+        }
+        assert_eq!(Arc::strong_count(&sphere), 4);
+        let world = BvhNode::new(list);
+        for x in [-3.0, 0.0, 3.0] {
+            let rec = hit(&world, &Ray::new(Point3::new(x, 0, 0), Vec3::new(0, 0, -1))).unwrap();
+            assert_eq!(rec.t, 4.0);
+        }
+        assert!(hit(
+            &world,
+            &Ray::new(Point3::new(1.5, 0, 0), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_singular_matrix() {
+        Transform::new(unit_sphere(), Mat4::scaling(&Vec3::new(1, 0, 1)));
+    }
+}
@@ -0,0 +1,188 @@
+use rand::RngCore;
+
+use crate::{
+    aabb::{Aabb, BBOX_PADDING},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    vec3::{Point3, Vec3},
+};
+
+/// Möller–Trumbore ray/triangle intersection. Returns the ray parameter and
+/// the barycentric coordinates of `v1` and `v2` at the hit point.
+pub(crate) fn intersect(
+    r: &Ray,
+    v0: &Point3,
+    v1: &Point3,
+    v2: &Point3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let pvec = r.direction().cross(&edge2);
+    let det = edge1.dot(&pvec);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let tvec = r.origin() - v0;
+    let u = tvec.dot(&pvec) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let qvec = tvec.cross(&edge1);
+    let v = r.direction().dot(&qvec) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(&qvec) * inv_det;
+    if t < t_max && t > t_min {
+        return Some((t, u, v));
+    }
+    return None;
+}
+
+pub(crate) fn bounding_box(v0: &Point3, v1: &Point3, v2: &Point3) -> Aabb {
+    Aabb::new(v0.min(v1).min(v2), v0.max(v1).max(v2)).padded(BBOX_PADDING)
+}
+
+pub struct Triangle {
+    vertices: [Point3; 3],
+    material: Box<dyn Material + 'static>,
+}
+
+impl Triangle {
+    pub fn new<M: Material + 'static>(v0: Point3, v1: Point3, v2: Point3, material: M) -> Self {
+        Triangle {
+            vertices: [v0, v1, v2],
+            material: Box::new(material),
+        }
+    }
+
+    pub fn vertices(&self) -> &[Point3; 3] {
+        &self.vertices
+    }
+
+    /// Unit normal, facing the side from which the vertices appear
+    /// counter-clockwise.
+    pub fn normal(&self) -> Vec3 {
+        let [v0, v1, v2] = &self.vertices;
+        (v1 - v0).cross(&(v2 - v0)).unit()
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        rec: &mut HitRecord,
+        _rng: &mut Box<dyn RngCore>,
+    ) -> bool {
+        let [v0, v1, v2] = &self.vertices;
+        match intersect(r, v0, v1, v2, t_min, t_max) {
+            Some((t, u, v)) => {
+                rec.t = t;
+                rec.u = u;
+                rec.v = v;
+                rec.p = r.at(t);
+                rec.set_face_normal(r, &self.normal());
+                rec.material = self.material.clone_box();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [v0, v1, v2] = &self.vertices;
+        Some(bounding_box(v0, v1, v2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Triangle;
+    use crate::{
+        hittable::{hit, Hittable},
+        material::lambertian::Lambertian,
+        ray::Ray,
+        vec3::{Point3, Vec3},
+    };
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Point3::new(0, 0, 0),
+            Point3::new(1, 0, 0),
+            Point3::new(0, 1, 0),
+            Lambertian::default(),
+        )
+    }
+
+    #[test]
+    fn hit_front() {
+        let rec = hit(
+            &triangle(),
+            &Ray::new(Point3::new(0.25, 0.25, 2), Vec3::new(0, 0, -1)),
+        )
+        .unwrap();
+        assert_eq!(rec.t, 2.0);
+        assert_eq!(rec.p, Point3::new(0.25, 0.25, 0));
+        assert_eq!(rec.normal, Vec3::new(0, 0, 1));
+        assert!(rec.front_face);
+        assert_eq!((rec.u, rec.v), (0.25, 0.25));
+    }
+
+    #[test]
+    fn hit_back() {
+        let rec = hit(
+            &triangle(),
+            &Ray::new(Point3::new(0.25, 0.25, -2), Vec3::new(0, 0, 1)),
+        )
+        .unwrap();
+        assert_eq!(rec.normal, Vec3::new(0, 0, -1));
+        assert!(!rec.front_face);
+    }
+
+    #[test]
+    fn miss_outside() {
+        assert!(hit(
+            &triangle(),
+            &Ray::new(Point3::new(0.6, 0.6, 2), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+        assert!(hit(
+            &triangle(),
+            &Ray::new(Point3::new(-0.1, 0.5, 2), Vec3::new(0, 0, -1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn miss_parallel() {
+        assert!(hit(
+            &triangle(),
+            &Ray::new(Point3::new(-1, 0.25, 0), Vec3::new(1, 0, 0))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn miss_behind() {
+        assert!(hit(
+            &triangle(),
+            &Ray::new(Point3::new(0.25, 0.25, 2), Vec3::new(0, 0, 1))
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn flat_bounding_box_is_padded() {
+        let bbox = triangle().bounding_box().unwrap();
+        assert!(bbox.max().z() > bbox.min().z());
+        let r = Ray::new(Point3::new(0.25, 0.25, 2), Vec3::new(0, 0, -1));
+        assert!(bbox.hit(&r, 0.001, f64::INFINITY));
+    }
+}
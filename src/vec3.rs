@@ -25,7 +25,7 @@ impl Vec3 {
     }
 
     pub fn length_squared(&self) -> f64 {
-        return self.dot(&self);
+        return self.dot(self);
     }
 
     pub fn length(&self) -> f64 {
@@ -48,6 +48,14 @@ impl Vec3 {
         return self / self.length();
     }
 
+    pub fn min(&self, rhs: &Self) -> Self {
+        Vec3(self.0.min(rhs.0), self.1.min(rhs.1), self.2.min(rhs.2))
+    }
+
+    pub fn max(&self, rhs: &Self) -> Self {
+        Vec3(self.0.max(rhs.0), self.1.max(rhs.1), self.2.max(rhs.2))
+    }
+
     pub fn to_rgb(&self) -> image::Rgb<u8> {
         let n256 = 256.0;
         return image::Rgb([
@@ -71,11 +79,105 @@ impl Vec3 {
 
     pub fn random_unit_vector<T: Rng>(rng: &mut T) -> Self {
         let a = rng.gen_range(0.0..(2.0 * PI));
-        let z = rng.gen_range(-1.0..1.0);
-        let r: f64 = (1.0 as f64 - z * z).sqrt();
+        let z: f64 = rng.gen_range(-1.0..1.0);
+        let r = (1.0 - z * z).sqrt();
         return Vec3::new(r * a.cos(), r * a.sin(), z);
     }
 
+    pub fn random_in_unit_disk<T: Rng + ?Sized>(rng: &mut T) -> Self {
+        loop {
+            let p = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0);
+            if p.length_squared() < 1.0 {
+                return p;
+            }
+        }
+    }
+
+    /// Density over solid angle of `random_unit_vector`, the same for every
+    /// direction.
+    pub fn unit_vector_pdf() -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    /// Direction in the hemisphere around +z, uniform over solid angle.
+    pub fn random_hemisphere_direction<T: Rng + ?Sized>(rng: &mut T) -> Self {
+        let z = 1.0 - rng.gen::<f64>();
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        let r = (1.0 - z * z).sqrt();
+        return Vec3::new(r * phi.cos(), r * phi.sin(), z);
+    }
+
+    /// Density over solid angle of `random_hemisphere_direction` at the
+    /// direction `self`.
+    pub fn hemisphere_direction_pdf(&self) -> f64 {
+        if self.z() > 0.0 {
+            1.0 / (2.0 * PI)
+        } else {
+            0.0
+        }
+    }
+
+    /// Direction in the hemisphere around +z with a density proportional to
+    /// the cosine of its angle to +z, as a diffuse surface facing +z
+    /// reflects light.
+    pub fn random_cosine_direction<T: Rng + ?Sized>(rng: &mut T) -> Self {
+        let p = Vec3::random_in_concentric_disk(rng);
+        let z = (1.0 - p.length_squared()).max(0.0).sqrt();
+        return Vec3::new(p.x(), p.y(), z);
+    }
+
+    /// Density over solid angle of `random_cosine_direction` at the
+    /// direction `self`.
+    pub fn cosine_direction_pdf(&self) -> f64 {
+        self.unit().z().max(0.0) / PI
+    }
+
+    /// Direction at most the angle whose cosine is `cos_theta_max` away from
+    /// +z, uniform over solid angle.
+    pub fn random_cone_direction<T: Rng + ?Sized>(cos_theta_max: f64, rng: &mut T) -> Self {
+        let z = 1.0 - rng.gen::<f64>() * (1.0 - cos_theta_max);
+        let phi = 2.0 * PI * rng.gen::<f64>();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        return Vec3::new(r * phi.cos(), r * phi.sin(), z);
+    }
+
+    /// Density over solid angle of `random_cone_direction` at the direction
+    /// `self`.
+    pub fn cone_direction_pdf(&self, cos_theta_max: f64) -> f64 {
+        if self.unit().z() >= cos_theta_max {
+            1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+        } else {
+            0.0
+        }
+    }
+
+    /// Point in the unit disk of the xy-plane, uniform over its area. The
+    /// square of random numbers is mapped onto the disk ring by ring, so
+    /// that samples spread evenly over the square stay evenly spread.
+    pub fn random_in_concentric_disk<T: Rng + ?Sized>(rng: &mut T) -> Self {
+        let a = 2.0 * rng.gen::<f64>() - 1.0;
+        let b = 2.0 * rng.gen::<f64>() - 1.0;
+        if a == 0.0 && b == 0.0 {
+            return Vec3::default();
+        }
+        let (r, phi) = if a.abs() > b.abs() {
+            (a, PI / 4.0 * (b / a))
+        } else {
+            (b, PI / 2.0 - PI / 4.0 * (a / b))
+        };
+        return Vec3::new(r * phi.cos(), r * phi.sin(), 0);
+    }
+
+    /// Density over area of `random_in_concentric_disk` at the point `self`
+    /// of the xy-plane.
+    pub fn concentric_disk_pdf(&self) -> f64 {
+        if self.x() * self.x() + self.y() * self.y() <= 1.0 {
+            1.0 / PI
+        } else {
+            0.0
+        }
+    }
+
     pub fn reflect(&self, n: &Self) -> Self {
         self - &(n * (self.dot(n) * 2.0))
     }
@@ -87,6 +189,18 @@ impl Vec3 {
     }
 }
 
+impl ops::Index<usize> for Vec3 {
+    type Output = f64;
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.0,
+            1 => &self.1,
+            2 => &self.2,
+            _ => panic!("Vec3 index out of range: {}", index),
+        }
+    }
+}
+
 impl ops::Neg for Vec3 {
     type Output = Vec3;
     fn neg(self) -> Self::Output {
@@ -204,6 +318,10 @@ pub type Color = Vec3;
 
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
     use crate::vec3::Vec3;
 
     #[test]
@@ -301,6 +419,145 @@ mod tests {
         )
     }
 
+    #[test]
+    fn min_max() {
+        let a = Vec3::new(0.0, 5.0, -1.0);
+        let b = Vec3::new(1.0, 2.0, -3.0);
+        assert_eq!(a.min(&b), Vec3::new(0.0, 2.0, -3.0));
+        assert_eq!(a.max(&b), Vec3::new(1.0, 5.0, -1.0));
+    }
+
+    #[test]
+    fn index() {
+        let vec = Vec3::new(0, 1, 2);
+        assert_eq!(vec[0], 0.0);
+        assert_eq!(vec[1], 1.0);
+        assert_eq!(vec[2], 2.0);
+    }
+
+    #[test]
+    fn random_in_unit_disk() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let p = Vec3::random_in_unit_disk(&mut rng);
+            assert!(p.length_squared() < 1.0);
+            assert_eq!(p.z(), 0.0);
+        }
+    }
+
+    /// Chi-square statistic of `values` in [0, 1) against the uniform
+    /// distribution, over 20 bins.
+    fn chi_square(values: impl Iterator<Item = f64>) -> f64 {
+        let mut bins = [0usize; 20];
+        let mut n = 0;
+        for value in values {
+            assert!((0.0..1.0).contains(&value), "{}", value);
+            bins[(value * 20.0) as usize] += 1;
+            n += 1;
+        }
+        let expected = n as f64 / 20.0;
+        return bins
+            .iter()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum();
+    }
+
+    /// Well above the 99.9th percentile of the chi-square distribution with
+    /// 19 degrees of freedom.
+    const CHI_SQUARE_LIMIT: f64 = 50.0;
+
+    /// Angle around z as a fraction of a turn.
+    fn turn(p: &Vec3) -> f64 {
+        (p.y().atan2(p.x()) / (2.0 * PI)).rem_euclid(1.0)
+    }
+
+    /// Checks that the samples of `random` are spread as the CDF `cdf` of
+    /// their z-coordinates says, evenly around z, and with density `pdf`,
+    /// the mean of whose inverse is the size of the domain, `measure`.
+    fn check_directions(
+        random: impl Fn(&mut StdRng) -> Vec3,
+        cdf: impl Fn(f64) -> f64,
+        pdf: impl Fn(&Vec3) -> f64,
+        measure: f64,
+    ) {
+        let mut rng = StdRng::seed_from_u64(1);
+        let samples: Vec<Vec3> = (0..20_000).map(|_| random(&mut rng)).collect();
+        for d in &samples {
+            assert!((d.length() - 1.0).abs() < 1e-9);
+        }
+        let heights = chi_square(samples.iter().map(|d| cdf(d.z()).min(1.0 - 1e-12)));
+        assert!(heights < CHI_SQUARE_LIMIT, "{}", heights);
+        let turns = chi_square(samples.iter().map(turn));
+        assert!(turns < CHI_SQUARE_LIMIT, "{}", turns);
+        let inverse: f64 = samples.iter().map(|d| 1.0 / pdf(d)).sum::<f64>() / 20_000.0;
+        assert!((inverse / measure - 1.0).abs() < 1e-2, "{}", inverse);
+    }
+
+    #[test]
+    fn random_unit_vector() {
+        check_directions(
+            Vec3::random_unit_vector,
+            |z| (z + 1.0) / 2.0,
+            |_| Vec3::unit_vector_pdf(),
+            4.0 * PI,
+        );
+    }
+
+    #[test]
+    fn random_hemisphere_direction() {
+        check_directions(
+            Vec3::random_hemisphere_direction,
+            |z| z,
+            Vec3::hemisphere_direction_pdf,
+            2.0 * PI,
+        );
+        assert_eq!(Vec3::new(1, 0, -1).hemisphere_direction_pdf(), 0.0);
+    }
+
+    #[test]
+    fn random_cosine_direction() {
+        // The cosine z of the angle to +z has density 2z.
+        check_directions(
+            Vec3::random_cosine_direction,
+            |z| z * z,
+            Vec3::cosine_direction_pdf,
+            2.0 * PI,
+        );
+        assert_eq!(Vec3::new(0, 0, 3).cosine_direction_pdf(), 1.0 / PI);
+        assert_eq!(Vec3::new(1, 0, -1).cosine_direction_pdf(), 0.0);
+    }
+
+    #[test]
+    fn random_cone_direction() {
+        let cos_theta_max = 0.8;
+        check_directions(
+            |rng| Vec3::random_cone_direction(cos_theta_max, rng),
+            |z| (z - cos_theta_max) / (1.0 - cos_theta_max),
+            |d| d.cone_direction_pdf(cos_theta_max),
+            2.0 * PI * (1.0 - cos_theta_max),
+        );
+        assert_eq!(Vec3::new(1, 0, 1).cone_direction_pdf(cos_theta_max), 0.0);
+    }
+
+    #[test]
+    fn random_in_concentric_disk() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let samples: Vec<Vec3> = (0..20_000)
+            .map(|_| Vec3::random_in_concentric_disk(&mut rng))
+            .collect();
+        for p in &samples {
+            assert!(p.length_squared() <= 1.0 && p.z() == 0.0);
+        }
+        // Uniform over area: the squared radius is uniform too.
+        let radii = chi_square(samples.iter().map(|p| p.length_squared().min(1.0 - 1e-12)));
+        assert!(radii < CHI_SQUARE_LIMIT, "{}", radii);
+        let turns = chi_square(samples.iter().map(turn));
+        assert!(turns < CHI_SQUARE_LIMIT, "{}", turns);
+        let inverse: f64 = samples.iter().map(|p| 1.0 / p.concentric_disk_pdf()).sum();
+        assert!((inverse / 20_000.0 - PI).abs() < 1e-12);
+        assert_eq!(Vec3::new(1, 1, 0).concentric_disk_pdf(), 0.0);
+    }
+
     #[test]
     fn display() {
         assert_eq!(format!("{}", Vec3::new(0.0, 1.0, 12.0)), "0.00 1.00 12.00")
@@ -0,0 +1,359 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::vec3::Point3;
+
+#[derive(Debug)]
+pub enum GridError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Format {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            GridError::Format { path, message } => write!(f, "{}: {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for GridError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GridError::Io { source, .. } => Some(source),
+            GridError::Format { .. } => None,
+        }
+    }
+}
+
+/// Quantities a grid can store per voxel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Density,
+    /// In kelvin.
+    Temperature,
+    /// Strength of the light given off.
+    Emission,
+}
+
+impl Channel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Density => "density",
+            Channel::Temperature => "temperature",
+            Channel::Emission => "emission",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Channel> {
+        [Channel::Density, Channel::Temperature, Channel::Emission]
+            .into_iter()
+            .find(|channel| channel.name() == name)
+    }
+}
+
+/// Dense 3D grid of voxel values, such as the density of smoke from a
+/// simulation. Values sit at the voxel centers of the unit cube and are
+/// interpolated trilinearly in between.
+///
+/// On disk a grid is one ASCII header line, `grid <nx> <ny> <nz>` followed
+/// by the names of its channels, then for each channel in turn the raw
+/// little-endian `f32` values with x varying fastest and z slowest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid {
+    size: [usize; 3],
+    channels: Vec<(Channel, Vec<f32>)>,
+}
+
+impl VoxelGrid {
+    /// Grid of `size` voxels with a density channel. Panics unless there is
+    /// one value per voxel.
+    pub fn new(size: [usize; 3], density: Vec<f32>) -> Self {
+        VoxelGrid {
+            size,
+            channels: vec![],
+        }
+        .with_channel(Channel::Density, density)
+    }
+
+    /// Adds or replaces a channel. Panics unless there is one value per
+    /// voxel.
+    pub fn with_channel(mut self, channel: Channel, values: Vec<f32>) -> Self {
+        assert_eq!(
+            values.len(),
+            self.len(),
+            "{} values do not match the grid size",
+            channel.name()
+        );
+        self.channels.retain(|(c, _)| *c != channel);
+        self.channels.push((channel, values));
+        self
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GridError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|source| GridError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::parse(&bytes, path)
+    }
+
+    /// Reads a grid from the bytes of a grid file; `path` is for errors.
+    pub fn parse(bytes: &[u8], path: &Path) -> Result<Self, GridError> {
+        let error = |message: String| GridError::Format {
+            path: path.to_owned(),
+            message,
+        };
+        let end = bytes
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or_else(|| error("missing header line".into()))?;
+        let header =
+            std::str::from_utf8(&bytes[..end]).map_err(|_| error("header is not text".into()))?;
+        let mut words = header.split_whitespace();
+        if words.next() != Some("grid") {
+            return Err(error("header must start with `grid`".into()));
+        }
+        let mut size = [0; 3];
+        for n in size.iter_mut() {
+            let word = words.next().unwrap_or("");
+            *n = match word.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(error(format!("bad grid size `{}`", word))),
+            };
+        }
+        let mut names = vec![];
+        for word in words {
+            let channel = Channel::from_name(word)
+                .ok_or_else(|| error(format!("unknown channel `{}`", word)))?;
+            if names.contains(&channel) {
+                return Err(error(format!("channel `{}` appears twice", word)));
+            }
+            names.push(channel);
+        }
+        if !names.contains(&Channel::Density) {
+            return Err(error("grid has no density channel".into()));
+        }
+
+        let count = size
+            .iter()
+            .try_fold(1usize, |acc, n| acc.checked_mul(*n))
+            .filter(|count| count.checked_mul(4 * names.len()).is_some())
+            .ok_or_else(|| error("grid is too large".into()))?;
+        let data = &bytes[end + 1..];
+        if data.len() != count * 4 * names.len() {
+            return Err(error(format!(
+                "expected {} bytes of voxel data, found {}",
+                count * 4 * names.len(),
+                data.len()
+            )));
+        }
+        let mut channels = vec![];
+        for (i, channel) in names.into_iter().enumerate() {
+            let values = data[i * count * 4..(i + 1) * count * 4]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            channels.push((channel, values));
+        }
+        Ok(VoxelGrid { size, channels })
+    }
+
+    /// The grid in the file format `parse` reads.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let names: Vec<&str> = self.channels.iter().map(|(c, _)| c.name()).collect();
+        let mut bytes = format!(
+            "grid {} {} {} {}\n",
+            self.size[0],
+            self.size[1],
+            self.size[2],
+            names.join(" ")
+        )
+        .into_bytes();
+        for (_, values) in &self.channels {
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Voxels along x, y and z.
+    pub fn size(&self) -> [usize; 3] {
+        self.size
+    }
+
+    fn len(&self) -> usize {
+        self.size.iter().product()
+    }
+
+    pub fn has(&self, channel: Channel) -> bool {
+        self.values(channel).is_some()
+    }
+
+    fn values(&self, channel: Channel) -> Option<&[f32]> {
+        self.channels
+            .iter()
+            .find(|(c, _)| *c == channel)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Largest value of `channel`, or zero if the grid does not have it.
+    pub fn max(&self, channel: Channel) -> f64 {
+        self.values(channel).map_or(0.0, |values| {
+            values.iter().fold(0.0, |m: f64, v| m.max(*v as f64))
+        })
+    }
+
+    /// Value of `channel` at `p` in the unit cube, interpolated between the
+    /// eight nearest voxel centers. Beyond the outermost centers the values
+    /// of the border voxels extend to the faces of the cube; outside the
+    /// cube, and for missing channels, it is zero.
+    pub fn sample(&self, channel: Channel, p: &Point3) -> f64 {
+        let values = match self.values(channel) {
+            Some(values) => values,
+            None => return 0.0,
+        };
+        if (0..3).any(|axis| !(0.0..=1.0).contains(&p[axis])) {
+            return 0.0;
+        }
+        // Lower corner index and weight of the upper one along each axis.
+        let mut corner = [0; 3];
+        let mut weight = [0.0; 3];
+        for axis in 0..3 {
+            let n = self.size[axis];
+            let x = (p[axis] * n as f64 - 0.5).clamp(0.0, (n - 1) as f64);
+            corner[axis] = (x.floor() as usize).min(n.saturating_sub(2));
+            weight[axis] = x - corner[axis] as f64;
+        }
+        let [nx, ny, _] = self.size;
+        let mut value = 0.0;
+        for dz in 0..2 {
+            for dy in 0..2 {
+                for dx in 0..2 {
+                    let offset = [dx, dy, dz];
+                    let mut w = 1.0;
+                    let mut index = [0; 3];
+                    for axis in 0..3 {
+                        let upper = offset[axis] == 1;
+                        if upper && self.size[axis] == 1 {
+                            w = 0.0;
+                        }
+                        index[axis] = corner[axis] + offset[axis];
+                        w *= if upper {
+                            weight[axis]
+                        } else {
+                            1.0 - weight[axis]
+                        };
+                    }
+                    if w > 0.0 {
+                        let i = index[0] + nx * (index[1] + ny * index[2]);
+                        value += w * values[i] as f64;
+                    }
+                }
+            }
+        }
+        return value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Channel, GridError, VoxelGrid};
+    use crate::vec3::Point3;
+
+    fn parse(bytes: &[u8]) -> Result<VoxelGrid, GridError> {
+        VoxelGrid::parse(bytes, Path::new("smoke.grid"))
+    }
+
+    fn format_error(bytes: &[u8]) -> String {
+        match parse(bytes) {
+            Err(GridError::Format { message, .. }) => message,
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("grid should not load"),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let grid = VoxelGrid::new([2, 1, 3], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0])
+            .with_channel(Channel::Temperature, vec![1500.0; 6]);
+        let bytes = grid.to_bytes();
+        assert!(bytes.starts_with(b"grid 2 1 3 density temperature\n"));
+        assert_eq!(parse(&bytes).unwrap(), grid);
+        assert_eq!(grid.size(), [2, 1, 3]);
+        assert_eq!(grid.max(Channel::Density), 5.0);
+        assert_eq!(grid.max(Channel::Emission), 0.0);
+        assert!(grid.has(Channel::Temperature) && !grid.has(Channel::Emission));
+    }
+
+    #[test]
+    fn bad_files() {
+        assert_eq!(format_error(b"grid 1 1 1 density"), "missing header line");
+        assert_eq!(
+            format_error(b"voxels 1 1 1 density\n\0\0\0\0"),
+            "header must start with `grid`"
+        );
+        assert_eq!(format_error(b"grid 1 0 1 density\n"), "bad grid size `0`");
+        assert_eq!(
+            format_error(b"grid 1 1 density\n"),
+            "bad grid size `density`"
+        );
+        assert_eq!(
+            format_error(b"grid 1 1 1 density smoke\n"),
+            "unknown channel `smoke`"
+        );
+        assert_eq!(
+            format_error(b"grid 1 1 1 emission\n\0\0\0\0"),
+            "grid has no density channel"
+        );
+        assert_eq!(
+            format_error(b"grid 2 1 1 density\n\0\0\0\0"),
+            "expected 8 bytes of voxel data, found 4"
+        );
+        assert_eq!(
+            format_error(b"grid 99999999999 99999999999 99999999999 density\n"),
+            "grid is too large"
+        );
+        let missing = VoxelGrid::load("does-not-exist.grid");
+        assert!(matches!(missing, Err(GridError::Io { .. })));
+    }
+
+    #[test]
+    fn trilinear_sampling() {
+        // Voxel centers at x = 0.25 and 0.75.
+        let grid = VoxelGrid::new([2, 1, 1], vec![1.0, 3.0]);
+        let at = |x: f64, y: f64| grid.sample(Channel::Density, &Point3::new(x, y, 0.5));
+        assert_eq!(at(0.25, 0.5), 1.0);
+        assert_eq!(at(0.75, 0.5), 3.0);
+        assert_eq!(at(0.5, 0.5), 2.0);
+        // Flat beyond the outer centers, zero outside the cube.
+        assert_eq!(at(0.0, 0.9), 1.0);
+        assert_eq!(at(1.0, 0.1), 3.0);
+        assert_eq!(at(1.1, 0.5), 0.0);
+        assert_eq!(
+            grid.sample(Channel::Temperature, &Point3::new(0.5, 0.5, 0.5)),
+            0.0
+        );
+
+        let cube = VoxelGrid::new([2, 2, 2], (0..8).map(|i| i as f32).collect());
+        // The center is the mean of all eight voxels.
+        let center = cube.sample(Channel::Density, &Point3::new(0.5, 0.5, 0.5));
+        assert!((center - 3.5).abs() < 1e-12);
+        // Index 5 is x = 1, y = 0, z = 1.
+        let corner = cube.sample(Channel::Density, &Point3::new(0.75, 0.25, 0.75));
+        assert!((corner - 5.0).abs() < 1e-12);
+    }
+}